@@ -0,0 +1,230 @@
+use crate::census::ObjectKind;
+use crate::patterns::{Pattern, Transform};
+
+// apgcodes as used by apgsearch and Catagolue: a prefix naming the kind of
+// object and its population or period (xs4_, xp2_, xq4_), then the cells in
+// extended Wechsler format.
+
+const COLUMN_DIGITS: &[u8; 32] = b"0123456789abcdefghijklmnopqrstuv";
+const RUN_DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+// The code for an object given each of its phases, or None for kinds that
+// have no code. Every phase is tried in all eight orientations and the
+// shortest encoding wins, ties going to the alphabetically first.
+pub fn encode(kind: ObjectKind, phases: &[Vec<(i32, i32)>]) -> Option<String> {
+    let prefix = match kind {
+        ObjectKind::StillLife => format!("xs{}", phases.first()?.len()),
+        ObjectKind::Oscillator => format!("xp{}", phases.len()),
+        ObjectKind::Spaceship => format!("xq{}", phases.len()),
+        ObjectKind::Unstable => return None,
+    };
+
+    let wechsler = phases
+        .iter()
+        .flat_map(|cells| {
+            Transform::ALL.iter().map(move |transform| {
+                let cells: Vec<_> = cells.iter().map(|&(x, y)| transform.apply(x, y)).collect();
+                wechsler(&cells)
+            })
+        })
+        .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))?;
+    Some(format!("{}_{}", prefix, wechsler))
+}
+
+// The cells of an xs, xp or xq code, as drawn by the code. Still life codes
+// must have the population their prefix gives.
+pub fn decode(code: &str) -> Result<Pattern, String> {
+    let code = code.trim();
+    let (prefix, body) = code
+        .split_once('_')
+        .ok_or_else(|| format!("\"{}\" is not an apgcode", code))?;
+    let number = match prefix.get(..2) {
+        Some("xs" | "xp" | "xq") => prefix[2..]
+            .parse::<u32>()
+            .ok()
+            .filter(|&number| number > 0)
+            .ok_or_else(|| format!("Invalid apgcode prefix \"{}\"", prefix))?,
+        _ => return Err(format!("Unsupported apgcode prefix \"{}\"", prefix)),
+    };
+
+    let cells = parse_wechsler(body)?;
+    if cells.is_empty() {
+        return Err(format!("\"{}\" has no live cells", code));
+    }
+    if prefix.starts_with("xs") && cells.len() as u32 != number {
+        return Err(format!(
+            "\"{}\" has {} cells, not {}",
+            code,
+            cells.len(),
+            number
+        ));
+    }
+    Ok(Pattern::new(code, cells))
+}
+
+// Extended Wechsler format: the bounding box is cut into strips five rows
+// tall, separated by 'z'. Each column of a strip is one digit with the top
+// row as the lowest bit, runs of empty columns are shortened to w (2), x (3)
+// or y0-yz (4-39), and empty columns at the end of a strip are dropped.
+pub fn wechsler(cells: &[(i32, i32)]) -> String {
+    let Some(&(first_x, first_y)) = cells.first() else {
+        return String::new();
+    };
+    let (min_x, min_y, max_x, max_y) = cells.iter().fold(
+        (first_x, first_y, first_x, first_y),
+        |(min_x, min_y, max_x, max_y), &(x, y)| {
+            (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+        },
+    );
+
+    let width = (max_x - min_x + 1) as usize;
+    let strips = (max_y - min_y) as usize / 5 + 1;
+    let mut columns = vec![vec![0u8; width]; strips];
+    for &(x, y) in cells {
+        let (x, y) = ((x - min_x) as usize, (y - min_y) as usize);
+        columns[y / 5][x] |= 1 << (y % 5);
+    }
+
+    let mut code = String::new();
+    for (index, strip) in columns.iter().enumerate() {
+        if index > 0 {
+            code.push('z');
+        }
+        let end = strip
+            .iter()
+            .rposition(|&column| column != 0)
+            .map_or(0, |i| i + 1);
+        let mut zeros = 0;
+        for &column in &strip[..end] {
+            if column == 0 {
+                zeros += 1;
+                continue;
+            }
+            push_zeros(&mut code, zeros);
+            zeros = 0;
+            code.push(COLUMN_DIGITS[column as usize] as char);
+        }
+    }
+    code
+}
+
+pub fn parse_wechsler(text: &str) -> Result<Vec<(i32, i32)>, String> {
+    let mut cells = Vec::new();
+    let (mut x, mut strip) = (0, 0);
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '0'..='9' | 'a'..='v' => {
+                let column = c.to_digit(32).unwrap_or_default();
+                for bit in 0..5 {
+                    if column & (1 << bit) != 0 {
+                        cells.push((x, strip * 5 + bit));
+                    }
+                }
+                x += 1;
+            }
+            'w' => x += 2,
+            'x' => x += 3,
+            'y' => {
+                let run = chars
+                    .next()
+                    .and_then(|c| c.to_digit(36))
+                    .ok_or_else(|| "'y' must be followed by a digit or letter".to_string())?;
+                x += 4 + run as i32;
+            }
+            'z' => {
+                x = 0;
+                strip += 1;
+            }
+            _ => return Err(format!("Unexpected '{}' in extended Wechsler code", c)),
+        }
+    }
+    Ok(cells)
+}
+
+fn push_zeros(code: &mut String, mut zeros: usize) {
+    while zeros > 0 {
+        let run = zeros.min(39);
+        match run {
+            1 => code.push('0'),
+            2 => code.push('w'),
+            3 => code.push('x'),
+            _ => {
+                code.push('y');
+                code.push(RUN_DIGITS[run - 4] as char);
+            }
+        }
+        zeros -= run;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::census::identify;
+    use crate::rule::Rule;
+
+    const OBJECTS: [(&str, &[(i32, i32)]); 6] = [
+        ("xs4_33", &[(0, 0), (1, 0), (0, 1), (1, 1)]),
+        ("xs6_696", &[(1, 0), (2, 0), (0, 1), (3, 1), (1, 2), (2, 2)]),
+        ("xp2_7", &[(0, 0), (1, 0), (2, 0)]),
+        ("xp2_7e", &[(1, 0), (2, 0), (3, 0), (0, 1), (1, 1), (2, 1)]),
+        ("xq4_153", &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]),
+        (
+            "xq4_6frc",
+            &[
+                (1, 0),
+                (4, 0),
+                (0, 1),
+                (0, 2),
+                (4, 2),
+                (0, 3),
+                (1, 3),
+                (2, 3),
+                (3, 3),
+            ],
+        ),
+    ];
+
+    #[test]
+    fn known_objects_get_their_catagolue_codes() {
+        for (code, cells) in OBJECTS {
+            let object = identify(cells.to_vec(), Rule::conway());
+            assert_eq!(object.apgcode.as_deref(), Some(code));
+        }
+        let blinker = [vec![(0, 0), (1, 0), (2, 0)], vec![(1, -1), (1, 0), (1, 1)]];
+        assert_eq!(
+            encode(ObjectKind::Oscillator, &blinker).as_deref(),
+            Some("xp2_7")
+        );
+        assert_eq!(encode(ObjectKind::Unstable, &blinker), None);
+    }
+
+    #[test]
+    fn decoded_codes_encode_back() {
+        for (code, _) in OBJECTS {
+            let pattern = decode(code).unwrap();
+            let object = identify(pattern.cells, Rule::conway());
+            assert_eq!(object.apgcode.as_deref(), Some(code));
+        }
+    }
+
+    #[test]
+    fn bad_codes_are_rejected() {
+        for code in [
+            "xs5_33", "xs4", "ov_33", "xp0_7", "xq4_15?", "xs4_y", "xp2_",
+        ] {
+            assert!(decode(code).is_err(), "{}", code);
+        }
+    }
+
+    #[test]
+    fn empty_columns_and_strips() {
+        assert_eq!(wechsler(&[(0, 0), (3, 0)]), "1w1");
+        assert_eq!(wechsler(&[(0, 0), (10, 0)]), "1y51");
+        assert_eq!(wechsler(&[(0, 0), (0, 5)]), "1z1");
+        for text in ["1w1", "1y51", "1z1", "7a7"] {
+            assert_eq!(wechsler(&parse_wechsler(text).unwrap()), text);
+        }
+    }
+}
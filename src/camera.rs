@@ -0,0 +1,206 @@
+const MIN_ZOOM: f64 = 0.25;
+const MAX_ZOOM: f64 = 1000.0;
+
+// Maps the canvas onto the state texture. Screen positions are canvas pixels
+// measured from the top-left corner; `offset` is the texture coordinate shown
+// at the bottom-left corner of the canvas and `zoom` 1.0 fits the whole grid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    pub offset_x: f64,
+    pub offset_y: f64,
+    pub zoom: f64,
+}
+
+impl Camera {
+    pub fn new() -> Self {
+        Camera {
+            offset_x: 0.0,
+            offset_y: 0.0,
+            zoom: 1.0,
+        }
+    }
+
+    pub fn pan(&mut self, dx: f64, dy: f64, canvas_width: f64, canvas_height: f64) {
+        self.offset_x -= dx / canvas_width / self.zoom;
+        self.offset_y += dy / canvas_height / self.zoom;
+        self.clamp();
+    }
+
+    pub fn zoom_at(
+        &mut self,
+        px: f64,
+        py: f64,
+        factor: f64,
+        canvas_width: f64,
+        canvas_height: f64,
+    ) {
+        let (u, v) = self.screen_to_texture(px, py, canvas_width, canvas_height);
+        let (sx, sy) = screen_fraction(px, py, canvas_width, canvas_height);
+
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.offset_x = u - sx / self.zoom;
+        self.offset_y = v - sy / self.zoom;
+        self.clamp();
+    }
+
+    // Keeps the centre of the canvas over the grid, so the grid cannot be
+    // panned or zoomed out of sight. Zooming in on a cell of the grid never
+    // needs it, so the point under the cursor stays put.
+    fn clamp(&mut self) {
+        let half = 0.5 / self.zoom;
+        self.offset_x = self.offset_x.clamp(-half, 1.0 - half);
+        self.offset_y = self.offset_y.clamp(-half, 1.0 - half);
+    }
+
+    pub fn screen_to_texture(
+        &self,
+        px: f64,
+        py: f64,
+        canvas_width: f64,
+        canvas_height: f64,
+    ) -> (f64, f64) {
+        let (sx, sy) = screen_fraction(px, py, canvas_width, canvas_height);
+        (
+            self.offset_x + sx / self.zoom,
+            self.offset_y + sy / self.zoom,
+        )
+    }
+
+    pub fn screen_to_cell(
+        &self,
+        px: f64,
+        py: f64,
+        canvas_width: f64,
+        canvas_height: f64,
+        grid_width: u32,
+        grid_height: u32,
+    ) -> Option<(u32, u32)> {
+        let (u, v) = self.screen_to_texture(px, py, canvas_width, canvas_height);
+        let x = (u * grid_width as f64).floor();
+        let y = (v * grid_height as f64).floor();
+
+        if x < 0.0 || y < 0.0 || x >= grid_width as f64 || y >= grid_height as f64 {
+            None
+        } else {
+            Some((x as u32, y as u32))
+        }
+    }
+
+    // Screen position of the centre of a cell.
+    pub fn cell_to_screen(
+        &self,
+        x: u32,
+        y: u32,
+        canvas_width: f64,
+        canvas_height: f64,
+        grid_width: u32,
+        grid_height: u32,
+    ) -> (f64, f64) {
+        let u = (x as f64 + 0.5) / grid_width as f64;
+        let v = (y as f64 + 0.5) / grid_height as f64;
+        (
+            (u - self.offset_x) * self.zoom * canvas_width,
+            (1.0 - (v - self.offset_y) * self.zoom) * canvas_height,
+        )
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera::new()
+    }
+}
+
+fn screen_fraction(px: f64, py: f64, canvas_width: f64, canvas_height: f64) -> (f64, f64) {
+    (px / canvas_width, 1.0 - py / canvas_height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CANVAS: (f64, f64) = (800.0, 600.0);
+    const GRID: (u32, u32) = (200, 100);
+
+    fn cell_at(camera: &Camera, px: f64, py: f64) -> Option<(u32, u32)> {
+        camera.screen_to_cell(px, py, CANVAS.0, CANVAS.1, GRID.0, GRID.1)
+    }
+
+    fn screen_of(camera: &Camera, x: u32, y: u32) -> (f64, f64) {
+        camera.cell_to_screen(x, y, CANVAS.0, CANVAS.1, GRID.0, GRID.1)
+    }
+
+    #[test]
+    fn zooming_keeps_the_point_under_the_cursor() {
+        let mut camera = Camera::new();
+        for (px, py, factor) in [
+            (100.0, 450.0, 2.0),
+            (613.0, 37.0, 7.5),
+            (400.0, 300.0, 1.25),
+        ] {
+            let before = camera.screen_to_texture(px, py, CANVAS.0, CANVAS.1);
+            let cell = cell_at(&camera, px, py);
+            camera.zoom_at(px, py, factor, CANVAS.0, CANVAS.1);
+            let after = camera.screen_to_texture(px, py, CANVAS.0, CANVAS.1);
+            assert!((before.0 - after.0).abs() < 1e-12 && (before.1 - after.1).abs() < 1e-12);
+            assert_eq!(cell_at(&camera, px, py), cell);
+        }
+        assert_eq!(camera.zoom, 18.75);
+
+        camera.zoom_at(0.0, 0.0, 1e9, CANVAS.0, CANVAS.1);
+        assert_eq!(camera.zoom, MAX_ZOOM);
+        camera.zoom_at(0.0, 0.0, 1e-9, CANVAS.0, CANVAS.1);
+        assert_eq!(camera.zoom, MIN_ZOOM);
+    }
+
+    #[test]
+    fn cells_map_to_the_screen_and_back() {
+        let mut camera = Camera::new();
+        camera.zoom_at(250.0, 120.0, 3.0, CANVAS.0, CANVAS.1);
+        camera.pan(-40.0, 25.0, CANVAS.0, CANVAS.1);
+
+        let mut visible = 0;
+        for (x, y) in [(0, 0), (199, 99), (60, 70), (90, 80), (75, 85)] {
+            let (px, py) = screen_of(&camera, x, y);
+            if (0.0..CANVAS.0).contains(&px) && (0.0..CANVAS.1).contains(&py) {
+                visible += 1;
+            }
+            assert_eq!(cell_at(&camera, px, py), Some((x, y)));
+        }
+        assert!(visible >= 3);
+
+        // Row 0 is drawn at the bottom of the canvas.
+        let camera = Camera::new();
+        assert!(screen_of(&camera, 0, 0).1 > screen_of(&camera, 0, 99).1);
+        assert_eq!(cell_at(&camera, 1.0, CANVAS.1 - 1.0), Some((0, 0)));
+        assert_eq!(cell_at(&camera, -1.0, 10.0), None);
+    }
+
+    #[test]
+    fn panning_stops_with_the_grid_under_the_centre() {
+        let (centre_x, centre_y) = (CANVAS.0 / 2.0, CANVAS.1 / 2.0);
+        for zoom in [MIN_ZOOM, 1.0, 40.0] {
+            let mut camera = Camera::new();
+            camera.zoom_at(centre_x, centre_y, zoom, CANVAS.0, CANVAS.1);
+            for (dx, dy) in [
+                (1e7, 0.0),
+                (-1e7, 0.0),
+                (0.0, 1e7),
+                (0.0, -1e7),
+                (1e7, -1e7),
+            ] {
+                camera.pan(dx, dy, CANVAS.0, CANVAS.1);
+                let (u, v) = camera.screen_to_texture(centre_x, centre_y, CANVAS.0, CANVAS.1);
+                assert!((0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v));
+            }
+        }
+
+        // Small drags inside the limits move the grid with the pointer.
+        let mut camera = Camera::new();
+        camera.zoom_at(centre_x, centre_y, 4.0, CANVAS.0, CANVAS.1);
+        let (px, py) = screen_of(&camera, 100, 50);
+        camera.pan(32.0, -24.0, CANVAS.0, CANVAS.1);
+        let (moved_x, moved_y) = screen_of(&camera, 100, 50);
+        assert!((moved_x - px - 32.0).abs() < 1e-9 && (moved_y - py + 24.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,350 @@
+// The built-in pattern library, stored as RLE and loaded into the registry
+// when the engine starts. Each entry records what the pattern is known to do;
+// tests/catalog.rs runs every pattern and checks it.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Behaviour {
+    // Returns to its starting shape after `period` generations (and not
+    // before), moved by (dx, dy). Still lifes have period 1.
+    Periodic {
+        period: u32,
+        dx: i32,
+        dy: i32,
+    },
+    // A periodic engine that moves by (dx, dy) every `period` generations (and
+    // not fewer) and keeps growing: guns, puffers, rakes and spacefillers.
+    Growing {
+        period: u32,
+        dx: i32,
+        dy: i32,
+        growth: Growth,
+    },
+    // Settles after `lifespan` generations with `population` cells left.
+    Methuselah {
+        lifespan: u32,
+        population: u32,
+    },
+}
+
+// How the population of a growing pattern increases once its exhaust has
+// settled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Growth {
+    // `cells` more every `every` generations: guns, puffers and rakes.
+    Linear { every: u32, cells: u32 },
+    // More every `every` generations than the time before, like a
+    // spacefiller.
+    Quadratic { every: u32 },
+}
+
+pub struct CatalogEntry {
+    pub name: &'static str,
+    pub category: &'static str,
+    pub description: &'static str,
+    pub rle: &'static str,
+    pub behaviour: Behaviour,
+}
+
+const fn still_life(
+    name: &'static str,
+    description: &'static str,
+    rle: &'static str,
+) -> CatalogEntry {
+    CatalogEntry {
+        name,
+        category: "Still lifes",
+        description,
+        rle,
+        behaviour: Behaviour::Periodic {
+            period: 1,
+            dx: 0,
+            dy: 0,
+        },
+    }
+}
+
+const fn oscillator(
+    name: &'static str,
+    description: &'static str,
+    rle: &'static str,
+    period: u32,
+) -> CatalogEntry {
+    CatalogEntry {
+        name,
+        category: "Oscillators",
+        description,
+        rle,
+        behaviour: Behaviour::Periodic {
+            period,
+            dx: 0,
+            dy: 0,
+        },
+    }
+}
+
+const fn spaceship(
+    name: &'static str,
+    description: &'static str,
+    rle: &'static str,
+    (period, dx, dy): (u32, i32, i32),
+) -> CatalogEntry {
+    CatalogEntry {
+        name,
+        category: "Spaceships",
+        description,
+        rle,
+        behaviour: Behaviour::Periodic { period, dx, dy },
+    }
+}
+
+const fn growing(
+    name: &'static str,
+    category: &'static str,
+    description: &'static str,
+    rle: &'static str,
+    (period, dx, dy): (u32, i32, i32),
+    growth: Growth,
+) -> CatalogEntry {
+    CatalogEntry {
+        name,
+        category,
+        description,
+        rle,
+        behaviour: Behaviour::Growing {
+            period,
+            dx,
+            dy,
+            growth,
+        },
+    }
+}
+
+const fn methuselah(
+    name: &'static str,
+    description: &'static str,
+    rle: &'static str,
+    lifespan: u32,
+    population: u32,
+) -> CatalogEntry {
+    CatalogEntry {
+        name,
+        category: "Methuselahs",
+        description,
+        rle,
+        behaviour: Behaviour::Methuselah {
+            lifespan,
+            population,
+        },
+    }
+}
+
+pub const CATALOG: &[CatalogEntry] = &[
+    still_life("Block", "The most common still life", "2o$2o!"),
+    still_life("Beehive", "Six-cell still life", "b2o$o2bo$b2o!"),
+    still_life("Loaf", "Seven-cell still life", "b2o$o2bo$bobo$2bo!"),
+    still_life("Boat", "Five-cell still life", "2o$obo$bo!"),
+    still_life("Ship", "Six-cell still life", "2o$obo$b2o!"),
+    still_life("Tub", "Four-cell still life", "bo$obo$bo!"),
+    still_life("Pond", "Eight-cell still life", "b2o$o2bo$o2bo$b2o!"),
+    still_life("Barge", "Extended tub", "bo$obo$bobo$2bo!"),
+    still_life("Long boat", "Extended boat", "2o$obo$bobo$2bo!"),
+    still_life("Mango", "Eight-cell still life", "b2o$o2bo$bo2bo$2b2o!"),
+    still_life("Snake", "Six-cell still life", "2obo$ob2o!"),
+    still_life(
+        "Aircraft carrier",
+        "Two hooks joined back to back",
+        "2o$o2bo$2b2o!",
+    ),
+    still_life(
+        "Eater 1",
+        "Eats gliders and other spaceships",
+        "2o$obo$2bo$2b2o!",
+    ),
+    oscillator("Blinker", "Line of three cells", "3o!", 2),
+    oscillator("Toad", "Two offset lines of three", "b3o$3o!", 2),
+    oscillator(
+        "Beacon",
+        "Two blocks sharing corner cells",
+        "2o$o$3bo$2b2o!",
+        2,
+    ),
+    oscillator("Clock", "Spinning four-cell core", "2bo$obo$bobo$bo!", 2),
+    oscillator(
+        "Pulsar",
+        "Most common period 3 oscillator",
+        "2b3o3b3o2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2$2b3o3b3o$o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+        3,
+    ),
+    oscillator(
+        "Caterer",
+        "Period 3 oscillator",
+        "2bo$o3b4o$o3bo$o$3bo$b2o!",
+        3,
+    ),
+    oscillator(
+        "Mold",
+        "Period 4 oscillator",
+        "3b2o$2bo2bo$o2bobo$4bo$ob2o$bo!",
+        4,
+    ),
+    oscillator(
+        "Octagon 2",
+        "Period 5 ring of eight diagonals",
+        "3b2o$2bo2bo$bo4bo$o6bo$o6bo$bo4bo$2bo2bo$3b2o!",
+        5,
+    ),
+    oscillator(
+        "Figure eight",
+        "Two blocks that take turns",
+        "2o$2obo$4bo$bo$2bob2o$4b2o!",
+        8,
+    ),
+    oscillator(
+        "Kok's galaxy",
+        "Four arms rotating around a centre",
+        "6ob2o$6ob2o$7b2o$2o5b2o$2o5b2o$2o5b2o$2o$2ob6o$2ob6o!",
+        8,
+    ),
+    oscillator(
+        "Tumbler",
+        "Two halves that flip over each other",
+        "bo5bo$obo3bobo$o2bobo2bo$2bo3bo$2b2ob2o!",
+        14,
+    ),
+    oscillator(
+        "Pentadecathlon",
+        "Row of ten cells evolving through period 15",
+        "2bo4bo$2ob4ob2o$2bo4bo!",
+        15,
+    ),
+    oscillator(
+        "Queen bee shuttle",
+        "Queen bee bouncing between two blocks",
+        "9bo$7bobo$6bobo$2o3bo2bo11b2o$2o4bobo11b2o$7bobo$9bo!",
+        30,
+    ),
+    spaceship(
+        "Glider",
+        "The smallest spaceship, moving diagonally at c/4",
+        "bo$2bo$3o!",
+        (4, 1, 1),
+    ),
+    spaceship(
+        "Lightweight spaceship",
+        "Orthogonal c/2 spaceship",
+        "bo2bo$o$o3bo$4o!",
+        (4, -2, 0),
+    ),
+    spaceship(
+        "Middleweight spaceship",
+        "Orthogonal c/2 spaceship",
+        "2bo$o3bo$5bo$o4bo$b5o!",
+        (4, 2, 0),
+    ),
+    spaceship(
+        "Heavyweight spaceship",
+        "Largest of the standard c/2 spaceships",
+        "2b2o$o4bo$6bo$o5bo$b6o!",
+        (4, 2, 0),
+    ),
+    spaceship(
+        "Copperhead",
+        "Slow orthogonal c/10 spaceship",
+        "b2o2b2o$3b2o$3b2o$obo2bobo$o6bo2$o6bo$b2o2b2o$2b4o2$3b2o$3b2o!",
+        (10, 0, -1),
+    ),
+    spaceship(
+        "Loafer",
+        "Orthogonal c/7 spaceship",
+        "b2o2bob2o$o2bo2b2o$bobo$2bo$8bo$6b3o$5bo$6bo$7b2o!",
+        (7, -1, 0),
+    ),
+    growing(
+        "Gosper glider gun",
+        "Guns",
+        "The first known gun, emitting a glider every 30 generations",
+        "24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!",
+        (30, 0, 0),
+        Growth::Linear {
+            every: 30,
+            cells: 5,
+        },
+    ),
+    growing(
+        "Simkin glider gun",
+        "Guns",
+        "Small gun emitting a glider every 120 generations",
+        "2o5b2o$2o5b2o2$4b2o$4b2o5$22b2ob2o$21bo5bo$21bo6bo2b2o$21b3o3bo3b2o$26bo4$20b2o$20bo$21b3o$23bo!",
+        (120, 0, 0),
+        Growth::Linear {
+            every: 120,
+            cells: 5,
+        },
+    ),
+    growing(
+        "Blinker puffer 1",
+        "Puffers",
+        "c/2 puffer leaving a row of blinkers",
+        "3bo$bo3bo$o$o4bo$5o4$b2o$2ob3o$b4o$2b2o2$5b2o$3bo4bo$2bo$2bo5bo$2b6o!",
+        (8, -4, 0),
+        Growth::Linear { every: 8, cells: 3 },
+    ),
+    growing(
+        "Puffer train",
+        "Puffers",
+        "Two lightweight spaceships escorting an engine whose exhaust takes thousands of generations to settle",
+        "3bo$4bo$o3bo$b4o4$o$b2o$2bo$2bo$bo3$3bo$4bo$o3bo$b4o!",
+        (140, 70, 0),
+        Growth::Linear {
+            every: 420,
+            cells: 1320,
+        },
+    ),
+    growing(
+        "Space rake",
+        "Rakes",
+        "c/2 spaceship firing a glider backwards every 20 generations",
+        "11b2o5b4o$9b2ob2o3bo3bo$9b4o8bo$10b2o5bo2bo2$8bo$7b2o8b2o$6bo9bo2bo$7b5o4bo2bo$8b4o3b2ob2o$11bo4b2o4$18b4o$o2bo13bo3bo$4bo16bo$o3bo12bo2bo$b4o!",
+        (20, 10, 0),
+        Growth::Linear {
+            every: 20,
+            cells: 5,
+        },
+    ),
+    growing(
+        "Max",
+        "Spacefillers",
+        "Fills the plane with still stripes, growing at c/2 in every direction",
+        "18bo$17b3o$12b3o4b2o$11bo2b3o2bob2o$10bo3bobo2bobo$10bo4bobobobob2o$12bo4bobo3b2o$4o5bobo4bo3bob3o$o3b2obob3ob2o9b2o$o5b2o5bo$bo2b2obo2bo2bob2o$7bobobobobobo5b4o$bo2b2obo2bo2bo2b2obob2o3bo$o5b2o3bobobo3b2o5bo$o3b2obob2o2bo2bo2bob2o2bo$4o5bobobobobobo$10b2obo2bo2bob2o2bo$13bo5b2o5bo$b2o9b2ob3obob2o3bo$2b3obo3bo4bobo5b4o$2b2o3bobo4bo$2b2obobobobo4bo$5bobo2bobo3bo$4b2obo2b3o2bo$6b2o4b3o$7b3o$8bo!",
+        (1, 0, 0),
+        Growth::Quadratic { every: 4 },
+    ),
+    methuselah(
+        "R-pentomino",
+        "Five cells that take 1103 generations to settle",
+        "b2o$2o$bo!",
+        1103,
+        116,
+    ),
+    methuselah(
+        "Acorn",
+        "Seven cells that take 5206 generations to settle",
+        "bo$3bo$2o2b3o!",
+        5206,
+        633,
+    ),
+    methuselah(
+        "Diehard",
+        "Vanishes after 130 generations",
+        "6bo$2o$bo3b3o!",
+        130,
+        0,
+    ),
+    methuselah(
+        "Rabbits",
+        "Nine cells that take 17331 generations to settle",
+        "o3b3o$3o2bo$bo!",
+        17331,
+        1744,
+    ),
+];
@@ -0,0 +1,395 @@
+use std::collections::{HashMap, HashSet};
+
+use wasm_bindgen::prelude::*;
+
+use crate::apgcode;
+use crate::rule::Rule;
+
+// Longest period looked for when classifying an object; anything that has
+// not repeated by then counts as unstable.
+pub const MAX_PERIOD: u32 = 1000;
+
+// Generations looked ahead when deciding which cells belong together, enough
+// for shuttles such as the queen bee (period 30) to reach their ends.
+pub const ENVELOPE_GENERATIONS: u32 = 64;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ObjectKind {
+    StillLife,
+    Oscillator,
+    Spaceship,
+    // Still changing, or made of parts too close together to separate.
+    Unstable,
+}
+
+impl ObjectKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ObjectKind::StillLife => "still life",
+            ObjectKind::Oscillator => "oscillator",
+            ObjectKind::Spaceship => "spaceship",
+            ObjectKind::Unstable => "unstable",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Object {
+    // Live cells at their grid coordinates.
+    pub cells: Vec<(i32, i32)>,
+    pub kind: ObjectKind,
+    // 0 for unstable objects.
+    pub period: u32,
+    // Displacement per period; non-zero only for spaceships.
+    pub dx: i32,
+    pub dy: i32,
+    pub apgcode: Option<String>,
+}
+
+// One row of the census: how many objects share an apgcode. Unstable objects
+// are counted together under an empty code.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct CensusEntry {
+    pub apgcode: String,
+    pub kind: ObjectKind,
+    pub period: u32,
+    pub count: u32,
+    // Live cells in all the counted objects together.
+    pub cells: u32,
+}
+
+struct Classification {
+    kind: ObjectKind,
+    period: u32,
+    dx: i32,
+    dy: i32,
+    phases: Vec<Vec<(i32, i32)>>,
+}
+
+// Splits the live cells of a grid (one byte per cell, row by row) into
+// objects. Cells that come within two cells of each other during the next
+// ENVELOPE_GENERATIONS generations can interact, so they are grouped first;
+// a group is then split into its 8-connected parts when each part keeps
+// evolving the same way on its own. The grid is treated as a plane, so
+// objects crossing a wrapping edge come out as two.
+pub fn objects(cells: &[u8], width: u32, rule: Rule) -> Vec<Object> {
+    let live: Vec<(i32, i32)> = cells
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| **cell != 0)
+        .map(|(index, _)| ((index as u32 % width) as i32, (index as u32 / width) as i32))
+        .collect();
+
+    // Every cell that is alive at some point in the envelope, labelled by
+    // the group it ends up in.
+    let mut envelope: HashSet<(i32, i32)> = live.iter().copied().collect();
+    let mut current = envelope.clone();
+    for _ in 0..ENVELOPE_GENERATIONS {
+        current = step(&current, rule);
+        envelope.extend(&current);
+    }
+    let envelope: Vec<(i32, i32)> = envelope.into_iter().collect();
+    let mut groups: HashMap<(i32, i32), usize> = HashMap::new();
+    for (group, component) in components(&envelope, 2).into_iter().enumerate() {
+        groups.extend(component.into_iter().map(|cell| (cell, group)));
+    }
+
+    let mut clusters: HashMap<usize, Vec<(i32, i32)>> = HashMap::new();
+    for cell in live {
+        clusters.entry(groups[&cell]).or_default().push(cell);
+    }
+    let mut clusters: Vec<(usize, Vec<(i32, i32)>)> = clusters.into_iter().collect();
+    clusters.sort_unstable_by_key(|(group, _)| *group);
+
+    clusters
+        .into_iter()
+        .flat_map(|(_, cluster)| split(cluster, rule))
+        .collect()
+}
+
+// Classifies the cells as a single object, however many parts it has.
+pub fn identify(cells: Vec<(i32, i32)>, rule: Rule) -> Object {
+    let classification = classify(&cells, rule);
+    object(cells, classification)
+}
+
+// Counts objects by apgcode, most common first.
+pub fn census(objects: &[Object]) -> Vec<CensusEntry> {
+    let mut entries: HashMap<String, CensusEntry> = HashMap::new();
+    for object in objects {
+        let apgcode = object.apgcode.clone().unwrap_or_default();
+        let entry = entries
+            .entry(apgcode.clone())
+            .or_insert_with(|| CensusEntry {
+                apgcode,
+                kind: object.kind,
+                period: object.period,
+                count: 0,
+                cells: 0,
+            });
+        entry.count += 1;
+        entry.cells += object.cells.len() as u32;
+    }
+
+    let mut entries: Vec<CensusEntry> = entries.into_values().collect();
+    entries.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.kind.cmp(&b.kind))
+            .then_with(|| a.apgcode.cmp(&b.apgcode))
+    });
+    entries
+}
+
+pub fn to_csv(entries: &[CensusEntry]) -> String {
+    let mut csv = String::from("apgcode,kind,period,count,cells\n");
+    for entry in entries {
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            entry.apgcode,
+            entry.kind.name(),
+            entry.period,
+            entry.count,
+            entry.cells
+        ));
+    }
+    csv
+}
+
+fn split(cluster: Vec<(i32, i32)>, rule: Rule) -> Vec<Object> {
+    let parts = components(&cluster, 1);
+    if parts.len() > 1 {
+        let classified: Vec<Classification> =
+            parts.iter().map(|part| classify(part, rule)).collect();
+        if independent(&cluster, &parts, &classified, rule) {
+            return parts
+                .into_iter()
+                .zip(classified)
+                .map(|(cells, classification)| object(cells, classification))
+                .collect();
+        }
+    }
+
+    let classification = classify(&cluster, rule);
+    vec![object(cluster, classification)]
+}
+
+fn object(cells: Vec<(i32, i32)>, classification: Classification) -> Object {
+    let apgcode = apgcode::encode(classification.kind, &classification.phases);
+    Object {
+        cells,
+        kind: classification.kind,
+        period: classification.period,
+        dx: classification.dx,
+        dy: classification.dy,
+        apgcode,
+    }
+}
+
+// Whether the parts, run separately, add up to the whole group for a full
+// common period.
+fn independent(
+    cluster: &[(i32, i32)],
+    parts: &[Vec<(i32, i32)>],
+    classified: &[Classification],
+    rule: Rule,
+) -> bool {
+    if classified
+        .iter()
+        .any(|classification| classification.kind == ObjectKind::Unstable)
+    {
+        return false;
+    }
+    let period = classified.iter().try_fold(1u32, |period, classification| {
+        let period = lcm(period, classification.period);
+        (period <= MAX_PERIOD).then_some(period)
+    });
+    let Some(period) = period else {
+        return false;
+    };
+
+    let mut whole: HashSet<(i32, i32)> = cluster.iter().copied().collect();
+    let mut parts: Vec<HashSet<(i32, i32)>> = parts
+        .iter()
+        .map(|part| part.iter().copied().collect())
+        .collect();
+    for _ in 0..period {
+        whole = step(&whole, rule);
+        for part in parts.iter_mut() {
+            *part = step(part, rule);
+        }
+        let union: HashSet<(i32, i32)> = parts.iter().flatten().copied().collect();
+        if union != whole {
+            return false;
+        }
+    }
+    true
+}
+
+// Runs the cells on their own until their shape comes back.
+fn classify(cells: &[(i32, i32)], rule: Rule) -> Classification {
+    let unstable = Classification {
+        kind: ObjectKind::Unstable,
+        period: 0,
+        dx: 0,
+        dy: 0,
+        phases: Vec::new(),
+    };
+
+    let mut current: HashSet<(i32, i32)> = cells.iter().copied().collect();
+    let (start, start_x, start_y) = shape(&current);
+    let mut phases = vec![start.clone()];
+    for period in 1..=MAX_PERIOD {
+        current = step(&current, rule);
+        if current.is_empty() || current.len() > 4 * start.len() + 64 {
+            return unstable;
+        }
+
+        let (phase, x, y) = shape(&current);
+        if phase == start {
+            let (dx, dy) = (x - start_x, y - start_y);
+            let kind = match (period, dx, dy) {
+                (_, dx, dy) if dx != 0 || dy != 0 => ObjectKind::Spaceship,
+                (1, _, _) => ObjectKind::StillLife,
+                _ => ObjectKind::Oscillator,
+            };
+            return Classification {
+                kind,
+                period,
+                dx,
+                dy,
+                phases,
+            };
+        }
+        phases.push(phase);
+    }
+    unstable
+}
+
+// The cells moved so their bounding box starts at (0, 0), sorted, and the
+// box's original corner.
+fn shape(cells: &HashSet<(i32, i32)>) -> (Vec<(i32, i32)>, i32, i32) {
+    let min_x = cells.iter().map(|&(x, _)| x).min().unwrap_or_default();
+    let min_y = cells.iter().map(|&(_, y)| y).min().unwrap_or_default();
+    let mut shape: Vec<(i32, i32)> = cells.iter().map(|&(x, y)| (x - min_x, y - min_y)).collect();
+    shape.sort_unstable();
+    (shape, min_x, min_y)
+}
+
+fn step(cells: &HashSet<(i32, i32)>, rule: Rule) -> HashSet<(i32, i32)> {
+    let mut neighbors: HashMap<(i32, i32), u8> = HashMap::new();
+    for &(x, y) in cells {
+        neighbors.entry((x, y)).or_default();
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if dx != 0 || dy != 0 {
+                    *neighbors.entry((x + dx, y + dy)).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    neighbors
+        .into_iter()
+        .filter(|&(cell, count)| rule.next_state(cells.contains(&cell), count))
+        .map(|(cell, _)| cell)
+        .collect()
+}
+
+// Groups cells that are within `reach` cells of each other (Chebyshev
+// distance).
+fn components(cells: &[(i32, i32)], reach: i32) -> Vec<Vec<(i32, i32)>> {
+    let mut unvisited: HashSet<(i32, i32)> = cells.iter().copied().collect();
+    let mut components = Vec::new();
+    for &cell in cells {
+        if !unvisited.remove(&cell) {
+            continue;
+        }
+
+        let mut component = vec![cell];
+        let mut next = 0;
+        while next < component.len() {
+            let (x, y) = component[next];
+            next += 1;
+            for dy in -reach..=reach {
+                for dx in -reach..=reach {
+                    if unvisited.remove(&(x + dx, y + dy)) {
+                        component.push((x + dx, y + dy));
+                    }
+                }
+            }
+        }
+        components.push(component);
+    }
+    components
+}
+
+fn lcm(a: u32, b: u32) -> u32 {
+    fn gcd(a: u32, b: u32) -> u32 {
+        if b == 0 { a } else { gcd(b, a % b) }
+    }
+    a / gcd(a, b) * b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(width: u32, height: u32, live: &[(u32, u32)]) -> Vec<u8> {
+        let mut cells = vec![0; (width * height) as usize];
+        for &(x, y) in live {
+            cells[(y * width + x) as usize] = 1;
+        }
+        cells
+    }
+
+    #[test]
+    fn block_and_glider_are_counted_apart() {
+        // The glider heads south-east, away from the block.
+        let block = [(1, 1), (2, 1), (1, 2), (2, 2)];
+        let glider = [(7, 6), (8, 7), (6, 8), (7, 8), (8, 8)];
+        let cells = grid(64, 64, &[&block[..], &glider[..]].concat());
+        let mut found = objects(&cells, 64, Rule::conway());
+        found.sort_by_key(|object| object.kind);
+        assert_eq!(found.len(), 2);
+
+        assert_eq!(found[0].kind, ObjectKind::StillLife);
+        assert_eq!(found[0].apgcode.as_deref(), Some("xs4_33"));
+        assert_eq!(found[0].cells.len(), 4);
+
+        assert_eq!(found[1].kind, ObjectKind::Spaceship);
+        assert_eq!(found[1].apgcode.as_deref(), Some("xq4_153"));
+        assert_eq!(found[1].period, 4);
+        assert_eq!((found[1].dx, found[1].dy), (1, 1));
+
+        let entries = census(&found);
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|entry| entry.count == 1));
+        let csv = to_csv(&entries);
+        assert!(csv.starts_with("apgcode,kind,period,count,cells\n"));
+        assert!(csv.contains("xs4_33,still life,1,1,4\n"));
+        assert!(csv.contains("xq4_153,spaceship,4,1,5\n"));
+    }
+
+    #[test]
+    fn identical_objects_share_an_entry() {
+        let blinkers = [(2, 2), (3, 2), (4, 2), (10, 2), (10, 3), (10, 4)];
+        let found = objects(&grid(16, 8, &blinkers), 16, Rule::conway());
+        let entries = census(&found);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].apgcode, "xp2_7");
+        assert_eq!(entries[0].kind, ObjectKind::Oscillator);
+        assert_eq!((entries[0].count, entries[0].cells), (2, 6));
+    }
+
+    #[test]
+    fn cells_still_changing_are_unstable() {
+        // A pre-block: three cells that become a block.
+        let object = identify(vec![(0, 0), (1, 0), (0, 1)], Rule::conway());
+        assert_eq!(object.kind, ObjectKind::Unstable);
+        assert_eq!(object.apgcode, None);
+        assert_eq!(object.period, 0);
+    }
+}
@@ -0,0 +1,236 @@
+use wasm_bindgen::prelude::*;
+
+// How the render pass colours live cells.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    // Each state in its palette colour.
+    #[default]
+    State,
+    // Live cells coloured by how many generations they have been alive.
+    Age,
+}
+
+// Colour components from 0 to 1, as the shaders take them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+impl Color {
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Color {
+            r: r as f32 / 255.0,
+            g: g as f32 / 255.0,
+            b: b as f32 / 255.0,
+        }
+    }
+
+    // Accepts CSS hex colours: "#rrggbb" or "#rgb", the '#' being optional.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let hex = text.trim().trim_start_matches('#');
+        let digits: Vec<u8> = hex
+            .chars()
+            .map(|c| c.to_digit(16).map(|digit| digit as u8))
+            .collect::<Option<_>>()
+            .ok_or_else(|| format!("Invalid colour \"{}\"", text))?;
+        match digits[..] {
+            [r, g, b] => Ok(Color::rgb(r * 17, g * 17, b * 17)),
+            [r1, r2, g1, g2, b1, b2] => Ok(Color::rgb(r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2)),
+            _ => Err(format!("Invalid colour \"{}\": expected #rrggbb", text)),
+        }
+    }
+
+    pub fn to_hex(&self) -> String {
+        let byte = |component: f32| (component * 255.0).round() as u8;
+        format!(
+            "#{:02x}{:02x}{:02x}",
+            byte(self.r),
+            byte(self.g),
+            byte(self.b)
+        )
+    }
+}
+
+// Colours for cell states 0 (dead) and 1 (alive), and for the states past 1
+// that multi-state rules will use. Only the render pass knows about those:
+// it reads a state texture R of n as state n, with alive staying 255, but
+// the compute and reduction passes and every read back take R up to 127 as
+// dead. Until a multi-state rule writes them, the extra colours are
+// never drawn.
+pub const MAX_STATES: usize = 16;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    // At least dead and alive; states without a colour use the last one.
+    pub states: Vec<Color>,
+    // Lines between cells, when they are drawn.
+    pub grid: Color,
+    // Around the grid.
+    pub background: Color,
+}
+
+impl Palette {
+    pub fn new(states: Vec<Color>, grid: Color, background: Color) -> Result<Self, String> {
+        if !(2..=MAX_STATES).contains(&states.len()) {
+            return Err(format!(
+                "A palette needs between 2 and {} state colours, not {}",
+                MAX_STATES,
+                states.len()
+            ));
+        }
+        Ok(Palette {
+            states,
+            grid,
+            background,
+        })
+    }
+
+    pub fn dead(&self) -> Color {
+        self.states[0]
+    }
+
+    pub fn alive(&self) -> Color {
+        self.states[1]
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Theme::default().palette()
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Theme {
+    // White cells on black, as the renderer has always drawn them.
+    #[default]
+    Classic,
+    // Green on black, matching the web UI.
+    Terminal,
+    // Dark cells on white.
+    Paper,
+    Amber,
+    Ocean,
+}
+
+impl Theme {
+    pub fn palette(&self) -> Palette {
+        let (alive, dead, grid, background) = match self {
+            Theme::Classic => (
+                Color::rgb(0xff, 0xff, 0xff),
+                Color::rgb(0x00, 0x00, 0x00),
+                Color::rgb(0x33, 0x33, 0x33),
+                Color::rgb(0x1a, 0x1a, 0x1a),
+            ),
+            Theme::Terminal => (
+                Color::rgb(0x00, 0xff, 0x41),
+                Color::rgb(0x0a, 0x0a, 0x0a),
+                Color::rgb(0x00, 0x3b, 0x00),
+                Color::rgb(0x00, 0x00, 0x00),
+            ),
+            Theme::Paper => (
+                Color::rgb(0x20, 0x20, 0x20),
+                Color::rgb(0xfa, 0xfa, 0xfa),
+                Color::rgb(0xd0, 0xd0, 0xd0),
+                Color::rgb(0xe0, 0xe0, 0xe0),
+            ),
+            Theme::Amber => (
+                Color::rgb(0xff, 0xb0, 0x00),
+                Color::rgb(0x1a, 0x0f, 0x00),
+                Color::rgb(0x3d, 0x26, 0x00),
+                Color::rgb(0x0d, 0x08, 0x00),
+            ),
+            Theme::Ocean => (
+                Color::rgb(0x7f, 0xdb, 0xff),
+                Color::rgb(0x00, 0x1f, 0x3f),
+                Color::rgb(0x0b, 0x3a, 0x66),
+                Color::rgb(0x00, 0x11, 0x1f),
+            ),
+        };
+        Palette {
+            states: vec![dead, alive],
+            grid,
+            background,
+        }
+    }
+}
+
+// Ages are counted in a byte of the state texture.
+pub const MAX_AGE: u32 = 255;
+
+pub const MAX_GRADIENT_STOPS: usize = 8;
+
+// Colours spread evenly from age 1 (just born) to `max_age`; older cells keep
+// the last colour.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    pub stops: Vec<Color>,
+    pub max_age: u32,
+}
+
+impl Gradient {
+    pub fn new(stops: Vec<Color>, max_age: u32) -> Result<Self, String> {
+        if stops.is_empty() || stops.len() > MAX_GRADIENT_STOPS {
+            return Err(format!(
+                "A gradient needs between 1 and {} colours, not {}",
+                MAX_GRADIENT_STOPS,
+                stops.len()
+            ));
+        }
+        if !(1..=MAX_AGE).contains(&max_age) {
+            return Err(format!(
+                "Gradient age {} is not between 1 and {}",
+                max_age, MAX_AGE
+            ));
+        }
+        Ok(Gradient { stops, max_age })
+    }
+}
+
+impl Default for Gradient {
+    fn default() -> Self {
+        Gradient {
+            stops: vec![
+                Color::rgb(0xff, 0xf3, 0xb0),
+                Color::rgb(0xff, 0x8c, 0x00),
+                Color::rgb(0x31, 0x1b, 0x92),
+            ],
+            max_age: 100,
+        }
+    }
+}
+
+// Generations since death are counted in a byte too, and a trail must have
+// faded out before the count stops.
+pub const MAX_TRAIL_LENGTH: u32 = MAX_AGE - 1;
+
+// Everything the render pass needs besides the cells and the camera.
+#[derive(Clone, Debug)]
+pub struct Appearance {
+    pub palette: Palette,
+    // Lines between cells, drawn once cells are big enough on screen.
+    pub grid_lines: bool,
+    pub mode: ColorMode,
+    pub gradient: Gradient,
+    // Generations a dead cell takes to fade from `trail_color` to dead; 0
+    // turns trails off.
+    pub trail_length: u32,
+    pub trail_color: Color,
+}
+
+impl Default for Appearance {
+    fn default() -> Self {
+        Appearance {
+            palette: Palette::default(),
+            grid_lines: false,
+            mode: ColorMode::default(),
+            gradient: Gradient::default(),
+            trail_length: 0,
+            trail_color: Color::rgb(0x1e, 0x90, 0xff),
+        }
+    }
+}
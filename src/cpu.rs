@@ -0,0 +1,236 @@
+use wasm_bindgen::prelude::*;
+
+use crate::rule::Rule;
+use crate::simulator::Simulator;
+use crate::stats::Statistics;
+use crate::topology::Topology;
+
+pub struct CpuSimulator {
+    cells: Vec<u8>,
+    // Holds the previous generation between steps.
+    next: Vec<u8>,
+    rule: Rule,
+    topology: Topology,
+    width: u32,
+    height: u32,
+}
+
+impl CpuSimulator {
+    pub fn new(width: u32, height: u32) -> Self {
+        let size = (width * height) as usize;
+        Self {
+            cells: vec![0; size],
+            next: vec![0; size],
+            rule: Rule::default(),
+            topology: Topology::default(),
+            width,
+            height,
+        }
+    }
+
+    pub fn cells(&self) -> &[u8] {
+        &self.cells
+    }
+
+    pub fn is_alive(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.cells[(y * self.width + x) as usize] != 0
+    }
+
+    fn neighbors(&self, x: u32, y: u32) -> u8 {
+        let mut count = 0;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if dx == 0 && dy == 0 {
+                    continue;
+                }
+                if let Some((nx, ny)) =
+                    self.topology
+                        .resolve(x as i64 + dx, y as i64 + dy, self.width, self.height)
+                {
+                    count += self.cells[(ny * self.width + nx) as usize];
+                }
+            }
+        }
+        count
+    }
+}
+
+impl Simulator for CpuSimulator {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn load(&mut self, cells: &[u8]) -> Result<(), JsValue> {
+        for (cell, &value) in self.cells.iter_mut().zip(cells) {
+            *cell = (value != 0) as u8;
+        }
+        self.next.copy_from_slice(&self.cells);
+        Ok(())
+    }
+
+    fn set_cell(&mut self, x: u32, y: u32, alive: bool) -> Result<(), JsValue> {
+        if x < self.width && y < self.height {
+            self.cells[(y * self.width + x) as usize] = alive as u8;
+        }
+        Ok(())
+    }
+
+    fn read_region(&self, x: u32, y: u32, width: u32, height: u32) -> Result<Vec<u8>, JsValue> {
+        let mut region = Vec::with_capacity((width * height) as usize);
+        for row in y..y + height {
+            let start = (row * self.width + x) as usize;
+            region.extend_from_slice(&self.cells[start..start + width as usize]);
+        }
+        Ok(region)
+    }
+
+    fn statistics(&self) -> Result<Statistics, JsValue> {
+        Ok(Statistics::from_cells(
+            &self.next,
+            &self.cells,
+            self.width,
+            self.height,
+        ))
+    }
+
+    fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+    }
+
+    fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    fn step(&mut self) -> Result<(), JsValue> {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let index = (y * self.width + x) as usize;
+                let neighbors = self.neighbors(x, y);
+                let alive = self.rule.next_state(self.cells[index] == 1, neighbors);
+                self.next[index] = alive as u8;
+            }
+        }
+
+        std::mem::swap(&mut self.cells, &mut self.next);
+
+        Ok(())
+    }
+
+    fn host_cells(&self) -> Option<&[u8]> {
+        Some(&self.cells)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOPOLOGIES: [Topology; 5] = [
+        Topology::Plane,
+        Topology::Torus,
+        Topology::KleinHorizontal,
+        Topology::KleinVertical,
+        Topology::CrossSurface,
+    ];
+
+    fn with_cells(topology: Topology, live: &[(u32, u32)]) -> CpuSimulator {
+        let mut simulator = CpuSimulator::new(8, 8);
+        simulator.set_topology(topology);
+        for &(x, y) in live {
+            simulator.set_cell(x, y, true).unwrap();
+        }
+        simulator
+    }
+
+    fn live_cells(simulator: &CpuSimulator) -> Vec<(u32, u32)> {
+        let mut live = Vec::new();
+        for y in 0..simulator.height() {
+            for x in 0..simulator.width() {
+                if simulator.is_alive(x, y) {
+                    live.push((x, y));
+                }
+            }
+        }
+        live
+    }
+
+    fn sorted(mut cells: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+        cells.sort_unstable_by_key(|&(x, y)| (y, x));
+        cells
+    }
+
+    #[test]
+    fn block_blinker_and_glider_away_from_the_edges() {
+        for topology in TOPOLOGIES {
+            let block = [(3, 3), (4, 3), (3, 4), (4, 4)];
+            let mut simulator = with_cells(topology, &block);
+            simulator.step().unwrap();
+            assert_eq!(live_cells(&simulator), block, "{:?}", topology);
+
+            let mut simulator = with_cells(topology, &[(3, 3), (4, 3), (5, 3)]);
+            simulator.step().unwrap();
+            assert_eq!(
+                live_cells(&simulator),
+                [(4, 2), (4, 3), (4, 4)],
+                "{:?}",
+                topology
+            );
+            simulator.step().unwrap();
+            assert_eq!(
+                live_cells(&simulator),
+                [(3, 3), (4, 3), (5, 3)],
+                "{:?}",
+                topology
+            );
+
+            let glider = [(2, 1), (3, 2), (1, 3), (2, 3), (3, 3)];
+            let mut simulator = with_cells(topology, &glider);
+            simulator.step_n(4).unwrap();
+            let moved: Vec<_> = glider.iter().map(|&(x, y)| (x + 1, y + 1)).collect();
+            assert_eq!(live_cells(&simulator), sorted(moved), "{:?}", topology);
+        }
+    }
+
+    // A blinker on the top row sticks out past the edge when it turns; the
+    // compute shader's getCell resolves those cells the same way.
+    #[test]
+    fn blinker_across_the_top_edge_follows_the_topology() {
+        for topology in TOPOLOGIES {
+            let mut simulator = with_cells(topology, &[(1, 0), (2, 0), (3, 0)]);
+            simulator.step().unwrap();
+            let expected = [(2, -1), (2, 0), (2, 1)]
+                .iter()
+                .filter_map(|&(x, y)| topology.resolve(x, y, 8, 8))
+                .collect();
+            assert_eq!(live_cells(&simulator), sorted(expected), "{:?}", topology);
+        }
+        let mut plane = with_cells(Topology::Plane, &[(1, 0), (2, 0), (3, 0)]);
+        plane.step().unwrap();
+        assert_eq!(live_cells(&plane), [(2, 0), (2, 1)]);
+        let mut klein = with_cells(Topology::KleinHorizontal, &[(1, 0), (2, 0), (3, 0)]);
+        klein.step().unwrap();
+        assert_eq!(live_cells(&klein), [(2, 0), (2, 1), (5, 7)]);
+    }
+
+    #[test]
+    fn glider_comes_back_round_a_torus() {
+        let glider = [(2, 1), (3, 2), (1, 3), (2, 3), (3, 3)];
+        let mut simulator = with_cells(Topology::Torus, &glider);
+        simulator.step_n(32).unwrap();
+        assert_eq!(live_cells(&simulator), sorted(glider.to_vec()));
+    }
+
+    #[test]
+    fn rule_changes_apply_to_the_next_step() {
+        // Under HighLife (B36/S23) a cell with six neighbours is born.
+        let live = [(2, 2), (3, 2), (4, 2), (2, 4), (3, 4), (4, 4)];
+        let mut simulator = with_cells(Topology::Plane, &live);
+        simulator.set_rule(Rule::parse("B36/S23").unwrap());
+        simulator.step().unwrap();
+        assert!(simulator.is_alive(3, 3));
+    }
+}
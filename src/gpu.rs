@@ -0,0 +1,369 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{
+    WebGlBuffer, WebGlFramebuffer, WebGlProgram, WebGlRenderingContext, WebGlTexture,
+    WebGlUniformLocation,
+};
+
+use crate::rule::Rule;
+use crate::shaders::{self, REDUCTION_BLOCK_SIZE};
+use crate::simulator::Simulator;
+use crate::stats::{Block, Statistics};
+use crate::texture::{self, TextureManager};
+use crate::topology::Topology;
+use crate::webgl;
+
+// Looked up once when the compute program is built instead of on every step.
+struct ComputeLocations {
+    texture: Option<WebGlUniformLocation>,
+    resolution: Option<WebGlUniformLocation>,
+    wrap: Option<WebGlUniformLocation>,
+    twist: Option<WebGlUniformLocation>,
+    birth: Option<WebGlUniformLocation>,
+    survival: Option<WebGlUniformLocation>,
+    position: u32,
+}
+
+impl ComputeLocations {
+    fn new(gl: &WebGlRenderingContext, program: &WebGlProgram) -> Result<Self, JsValue> {
+        let position = gl.get_attrib_location(program, "a_position");
+        if position == -1 {
+            return Err(JsValue::from_str("a_position attribute not found"));
+        }
+
+        Ok(ComputeLocations {
+            texture: gl.get_uniform_location(program, "u_texture"),
+            resolution: gl.get_uniform_location(program, "u_resolution"),
+            wrap: gl.get_uniform_location(program, "u_wrap"),
+            twist: gl.get_uniform_location(program, "u_twist"),
+            birth: gl.get_uniform_location(program, "u_birth"),
+            survival: gl.get_uniform_location(program, "u_survival"),
+            position: position as u32,
+        })
+    }
+}
+
+pub struct GpuSimulator {
+    gl: WebGlRenderingContext,
+    compute_program: WebGlProgram,
+    compute_locations: ComputeLocations,
+    reduction_program: WebGlProgram,
+    reduction_target: WebGlTexture,
+    current_state: WebGlTexture,
+    next_state: WebGlTexture,
+    quad_buffer: WebGlBuffer,
+    framebuffer: WebGlFramebuffer,
+    texture_manager: TextureManager,
+    rule: Rule,
+    topology: Topology,
+    width: u32,
+    height: u32,
+}
+
+impl GpuSimulator {
+    pub fn new(gl: &WebGlRenderingContext, width: u32, height: u32) -> Result<Self, JsValue> {
+        let current_state = webgl::create_texture(gl, width, height)?;
+        let next_state = webgl::create_texture(gl, width, height)?;
+
+        let framebuffer = gl
+            .create_framebuffer()
+            .ok_or_else(|| JsValue::from_str("Failed to create framebuffer"))?;
+
+        let quad_buffer = webgl::create_quad_buffer(gl)?;
+
+        let compute_program = shaders::create_compute_program(gl)?;
+        let compute_locations = ComputeLocations::new(gl, &compute_program)?;
+
+        let reduction_program = shaders::create_reduction_program(gl)?;
+        let (target_width, target_height) = reduction_size(width, height);
+        let reduction_target = webgl::create_texture(gl, target_width, target_height)?;
+
+        let texture_manager = TextureManager::new(gl.clone(), width, height);
+
+        Ok(GpuSimulator {
+            gl: gl.clone(),
+            compute_program,
+            compute_locations,
+            reduction_program,
+            reduction_target,
+            current_state,
+            next_state,
+            quad_buffer,
+            framebuffer,
+            texture_manager,
+            rule: Rule::default(),
+            topology: Topology::default(),
+            width,
+            height,
+        })
+    }
+}
+
+impl Simulator for GpuSimulator {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn load(&mut self, cells: &[u8]) -> Result<(), JsValue> {
+        let data = texture::cells_to_texture_data(cells);
+
+        // Recreate textures with new data
+        self.current_state = webgl::create_texture(&self.gl, self.width, self.height)?;
+        self.texture_manager
+            .upload_data(&self.current_state, &data)?;
+
+        self.next_state = webgl::create_texture(&self.gl, self.width, self.height)?;
+        self.texture_manager.upload_data(&self.next_state, &data)?;
+
+        Ok(())
+    }
+
+    fn set_cell(&mut self, x: u32, y: u32, alive: bool) -> Result<(), JsValue> {
+        if x >= self.width || y >= self.height {
+            return Ok(());
+        }
+
+        self.gl
+            .bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(&self.current_state));
+
+        let data = texture::texel(alive);
+
+        self.gl
+            .tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_u8_array(
+                WebGlRenderingContext::TEXTURE_2D,
+                0,
+                x as i32,
+                y as i32,
+                1,
+                1,
+                WebGlRenderingContext::RGBA,
+                WebGlRenderingContext::UNSIGNED_BYTE,
+                Some(&data),
+            )?;
+
+        Ok(())
+    }
+
+    fn read_region(&self, x: u32, y: u32, width: u32, height: u32) -> Result<Vec<u8>, JsValue> {
+        self.gl
+            .bind_framebuffer(WebGlRenderingContext::FRAMEBUFFER, Some(&self.framebuffer));
+        self.gl.framebuffer_texture_2d(
+            WebGlRenderingContext::FRAMEBUFFER,
+            WebGlRenderingContext::COLOR_ATTACHMENT0,
+            WebGlRenderingContext::TEXTURE_2D,
+            Some(&self.current_state),
+            0,
+        );
+
+        let mut data = vec![0u8; (width * height * 4) as usize];
+        self.gl.read_pixels_with_opt_u8_array(
+            x as i32,
+            y as i32,
+            width as i32,
+            height as i32,
+            WebGlRenderingContext::RGBA,
+            WebGlRenderingContext::UNSIGNED_BYTE,
+            Some(&mut data),
+        )?;
+
+        self.gl
+            .bind_framebuffer(WebGlRenderingContext::FRAMEBUFFER, None);
+
+        Ok(texture::texture_data_to_cells(&data))
+    }
+
+    fn statistics(&self) -> Result<Statistics, JsValue> {
+        let (target_width, target_height) = reduction_size(self.width, self.height);
+
+        self.gl
+            .bind_framebuffer(WebGlRenderingContext::FRAMEBUFFER, Some(&self.framebuffer));
+        self.gl.framebuffer_texture_2d(
+            WebGlRenderingContext::FRAMEBUFFER,
+            WebGlRenderingContext::COLOR_ATTACHMENT0,
+            WebGlRenderingContext::TEXTURE_2D,
+            Some(&self.reduction_target),
+            0,
+        );
+
+        self.gl
+            .viewport(0, 0, target_width as i32, target_height as i32);
+        self.gl.use_program(Some(&self.reduction_program));
+
+        // After a step the next state texture holds the previous generation.
+        self.gl.active_texture(WebGlRenderingContext::TEXTURE1);
+        self.gl
+            .bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(&self.next_state));
+        self.gl.active_texture(WebGlRenderingContext::TEXTURE0);
+        self.gl
+            .bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(&self.current_state));
+
+        let current_location = self
+            .gl
+            .get_uniform_location(&self.reduction_program, "u_current");
+        self.gl.uniform1i(current_location.as_ref(), 0);
+
+        let previous_location = self
+            .gl
+            .get_uniform_location(&self.reduction_program, "u_previous");
+        self.gl.uniform1i(previous_location.as_ref(), 1);
+
+        let resolution_location = self
+            .gl
+            .get_uniform_location(&self.reduction_program, "u_resolution");
+        self.gl.uniform2f(
+            resolution_location.as_ref(),
+            self.width as f32,
+            self.height as f32,
+        );
+
+        self.gl
+            .bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&self.quad_buffer));
+        let position_location = self
+            .gl
+            .get_attrib_location(&self.reduction_program, "a_position");
+        self.gl.enable_vertex_attrib_array(position_location as u32);
+        self.gl.vertex_attrib_pointer_with_i32(
+            position_location as u32,
+            2,
+            WebGlRenderingContext::FLOAT,
+            false,
+            0,
+            0,
+        );
+
+        self.gl.draw_arrays(WebGlRenderingContext::TRIANGLES, 0, 6);
+
+        let mut data = vec![0u8; (target_width * target_height * 4) as usize];
+        self.gl.read_pixels_with_opt_u8_array(
+            0,
+            0,
+            target_width as i32,
+            target_height as i32,
+            WebGlRenderingContext::RGBA,
+            WebGlRenderingContext::UNSIGNED_BYTE,
+            Some(&mut data),
+        )?;
+
+        self.gl
+            .bind_framebuffer(WebGlRenderingContext::FRAMEBUFFER, None);
+
+        let blocks: Vec<Block> = data
+            .chunks_exact(8)
+            .map(|texels| Block {
+                population: texels[0] as u32,
+                births: texels[1] as u32,
+                deaths: texels[2] as u32,
+                columns: texels[4] as u32,
+                rows: texels[5] as u32,
+            })
+            .collect();
+
+        Ok(Statistics::from_blocks(
+            &blocks,
+            target_width / 2,
+            REDUCTION_BLOCK_SIZE,
+            self.width,
+            self.height,
+        ))
+    }
+
+    fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+    }
+
+    fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    fn step(&mut self) -> Result<(), JsValue> {
+        self.step_n(1)
+    }
+
+    // Sets the program, uniforms and vertex state once and then only swaps
+    // the framebuffer attachment between generations.
+    fn step_n(&mut self, generations: u32) -> Result<(), JsValue> {
+        if generations == 0 {
+            return Ok(());
+        }
+
+        let locations = &self.compute_locations;
+
+        self.gl
+            .bind_framebuffer(WebGlRenderingContext::FRAMEBUFFER, Some(&self.framebuffer));
+        self.gl
+            .viewport(0, 0, self.width as i32, self.height as i32);
+        self.gl.use_program(Some(&self.compute_program));
+
+        self.gl.uniform1i(locations.texture.as_ref(), 0);
+        self.gl.uniform2f(
+            locations.resolution.as_ref(),
+            self.width as f32,
+            self.height as f32,
+        );
+
+        let (wrap_x, wrap_y) = self.topology.wraps();
+        self.gl.uniform2f(
+            locations.wrap.as_ref(),
+            wrap_x as u8 as f32,
+            wrap_y as u8 as f32,
+        );
+
+        let (twist_x, twist_y) = self.topology.twists();
+        self.gl.uniform2f(
+            locations.twist.as_ref(),
+            twist_x as u8 as f32,
+            twist_y as u8 as f32,
+        );
+
+        self.gl
+            .uniform1f(locations.birth.as_ref(), self.rule.birth as f32);
+        self.gl
+            .uniform1f(locations.survival.as_ref(), self.rule.survival as f32);
+
+        self.gl
+            .bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&self.quad_buffer));
+        self.gl.enable_vertex_attrib_array(locations.position);
+        self.gl.vertex_attrib_pointer_with_i32(
+            locations.position,
+            2,
+            WebGlRenderingContext::FLOAT,
+            false,
+            0,
+            0,
+        );
+
+        self.gl.active_texture(WebGlRenderingContext::TEXTURE0);
+
+        for _ in 0..generations {
+            self.gl.framebuffer_texture_2d(
+                WebGlRenderingContext::FRAMEBUFFER,
+                WebGlRenderingContext::COLOR_ATTACHMENT0,
+                WebGlRenderingContext::TEXTURE_2D,
+                Some(&self.next_state),
+                0,
+            );
+            self.gl
+                .bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(&self.current_state));
+
+            self.gl.draw_arrays(WebGlRenderingContext::TRIANGLES, 0, 6);
+
+            std::mem::swap(&mut self.current_state, &mut self.next_state);
+        }
+
+        Ok(())
+    }
+
+    fn texture(&self) -> Option<&WebGlTexture> {
+        Some(&self.current_state)
+    }
+}
+
+fn reduction_size(width: u32, height: u32) -> (u32, u32) {
+    (
+        width.div_ceil(REDUCTION_BLOCK_SIZE) * 2,
+        height.div_ceil(REDUCTION_BLOCK_SIZE),
+    )
+}
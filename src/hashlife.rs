@@ -0,0 +1,797 @@
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::patterns::Pattern;
+use crate::rule::Rule;
+use crate::simulator::Simulator;
+use crate::stats::Statistics;
+use crate::topology::Topology;
+
+pub type NodeId = u32;
+
+pub const DEAD: NodeId = 0;
+pub const ALIVE: NodeId = 1;
+
+// Deepest tree we build; keeps every coordinate comfortably inside an i64.
+pub const MAX_LEVEL: u32 = 60;
+
+// A 2^k step needs a root of level k + 3.
+pub const MAX_STEP_POW2: u32 = MAX_LEVEL - 3;
+
+// Nodes kept before the arena is rebuilt from the live tree between steps.
+const GARBAGE_LIMIT: usize = 1 << 22;
+
+const MIN_ROOT_LEVEL: u32 = 3;
+
+// A level-n node covers 2^n x 2^n cells. Leaves are level 0 and are always
+// DEAD or ALIVE; y grows southwards, matching the grid rows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Node {
+    pub level: u32,
+    pub nw: NodeId,
+    pub ne: NodeId,
+    pub sw: NodeId,
+    pub se: NodeId,
+}
+
+struct Entry {
+    node: Node,
+    population: u64,
+}
+
+// The nodes reachable from a root, copied out of the arena so they outlive
+// garbage collection. Children come before their parents and are numbered
+// with DEAD and ALIVE first, then the tree's own nodes from 2; the root is
+// the last node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tree {
+    nodes: Vec<Node>,
+    generation: u64,
+}
+
+impl Tree {
+    // Bytes held by the nodes.
+    pub fn size(&self) -> usize {
+        self.nodes.len() * std::mem::size_of::<Node>()
+    }
+}
+
+// Hash-consed quadtree over an unbounded plane. The root is centred on the
+// origin, so a level-n root covers [-2^(n-1), 2^(n-1)) on both axes.
+pub struct Universe {
+    nodes: Vec<Entry>,
+    index: HashMap<Node, NodeId>,
+    // Centre of a node advanced by 2^k generations, keyed by (node, k).
+    results: HashMap<(NodeId, u32), NodeId>,
+    empty: Vec<NodeId>,
+    root: NodeId,
+    rule: Rule,
+    generation: u64,
+}
+
+impl Universe {
+    pub fn new() -> Self {
+        let mut universe = Universe {
+            nodes: Vec::new(),
+            index: HashMap::new(),
+            results: HashMap::new(),
+            empty: Vec::new(),
+            root: DEAD,
+            rule: Rule::default(),
+            generation: 0,
+        };
+        universe.reset();
+        universe
+    }
+
+    fn reset(&mut self) {
+        self.nodes.clear();
+        self.index.clear();
+        self.results.clear();
+        self.empty.clear();
+        for population in 0..2 {
+            self.nodes.push(Entry {
+                node: Node {
+                    level: 0,
+                    nw: DEAD,
+                    ne: DEAD,
+                    sw: DEAD,
+                    se: DEAD,
+                },
+                population,
+            });
+        }
+        self.root = self.empty(MIN_ROOT_LEVEL);
+    }
+
+    pub fn clear(&mut self) {
+        self.reset();
+        self.generation = 0;
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    // Rules with B0 would fill the infinite dead background and are rejected
+    // by callers before they get here.
+    pub fn set_rule(&mut self, rule: Rule) {
+        if rule != self.rule {
+            self.rule = rule;
+            self.results.clear();
+        }
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn set_generation(&mut self, generation: u64) {
+        self.generation = generation;
+    }
+
+    pub fn population(&self) -> u64 {
+        self.nodes[self.root as usize].population
+    }
+
+    pub fn root(&self) -> NodeId {
+        self.root
+    }
+
+    pub fn node(&self, id: NodeId) -> Node {
+        self.nodes[id as usize].node
+    }
+
+    pub fn node_population(&self, id: NodeId) -> u64 {
+        self.nodes[id as usize].population
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn to_tree(&self) -> Tree {
+        let mut tree = Tree {
+            nodes: Vec::new(),
+            generation: self.generation,
+        };
+        let mut numbers = HashMap::from([(DEAD, DEAD), (ALIVE, ALIVE)]);
+        self.copy_to_tree(self.root, &mut tree, &mut numbers);
+        tree
+    }
+
+    fn copy_to_tree(
+        &self,
+        id: NodeId,
+        tree: &mut Tree,
+        numbers: &mut HashMap<NodeId, NodeId>,
+    ) -> NodeId {
+        if let Some(&number) = numbers.get(&id) {
+            return number;
+        }
+
+        let node = self.node(id);
+        let copy = Node {
+            level: node.level,
+            nw: self.copy_to_tree(node.nw, tree, numbers),
+            ne: self.copy_to_tree(node.ne, tree, numbers),
+            sw: self.copy_to_tree(node.sw, tree, numbers),
+            se: self.copy_to_tree(node.se, tree, numbers),
+        };
+        let number = tree.nodes.len() as NodeId + 2;
+        tree.nodes.push(copy);
+        numbers.insert(id, number);
+        number
+    }
+
+    // A universe holding the tree's cells and generation, under `rule`.
+    pub fn from_tree(tree: &Tree, rule: Rule) -> Self {
+        let mut universe = Universe::new();
+        universe.set_rule(rule);
+        let mut ids = vec![DEAD, ALIVE];
+        for node in &tree.nodes {
+            let id = universe.join(
+                ids[node.nw as usize],
+                ids[node.ne as usize],
+                ids[node.sw as usize],
+                ids[node.se as usize],
+            );
+            ids.push(id);
+        }
+        if !tree.nodes.is_empty() {
+            universe.set_root(ids[ids.len() - 1]);
+        }
+        universe.generation = tree.generation;
+        universe
+    }
+
+    // Replaces the whole universe with `root`, a node built with `join`.
+    pub fn set_root(&mut self, root: NodeId) {
+        self.root = root;
+        while self.level(self.root) < MIN_ROOT_LEVEL {
+            self.expand();
+        }
+    }
+
+    pub fn join(&mut self, nw: NodeId, ne: NodeId, sw: NodeId, se: NodeId) -> NodeId {
+        let node = Node {
+            level: self.level(nw) + 1,
+            nw,
+            ne,
+            sw,
+            se,
+        };
+        if let Some(&id) = self.index.get(&node) {
+            return id;
+        }
+
+        let population = [nw, ne, sw, se]
+            .iter()
+            .map(|&child| self.nodes[child as usize].population)
+            .sum();
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(Entry { node, population });
+        self.index.insert(node, id);
+        id
+    }
+
+    pub fn empty(&mut self, level: u32) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let id = match self.empty.last() {
+                Some(&child) => self.join(child, child, child, child),
+                None => DEAD,
+            };
+            self.empty.push(id);
+        }
+        self.empty[level as usize]
+    }
+
+    fn level(&self, id: NodeId) -> u32 {
+        self.nodes[id as usize].node.level
+    }
+
+    fn half(&self) -> i64 {
+        1 << (self.level(self.root) - 1)
+    }
+
+    fn contains(&self, x: i64, y: i64) -> bool {
+        let half = self.half();
+        (-half..half).contains(&x) && (-half..half).contains(&y)
+    }
+
+    // Doubles the root around its centre.
+    fn expand(&mut self) {
+        let root = self.node(self.root);
+        let border = self.empty(root.level - 1);
+        let nw = self.join(border, border, border, root.nw);
+        let ne = self.join(border, border, root.ne, border);
+        let sw = self.join(border, root.sw, border, border);
+        let se = self.join(root.se, border, border, border);
+        self.root = self.join(nw, ne, sw, se);
+    }
+
+    pub fn get_cell(&self, x: i64, y: i64) -> bool {
+        if !self.contains(x, y) {
+            return false;
+        }
+
+        let half = self.half();
+        let (mut x, mut y) = (x + half, y + half);
+        let mut id = self.root;
+        while self.level(id) > 0 {
+            let node = self.node(id);
+            let half = 1 << (node.level - 1);
+            id = match (x >= half, y >= half) {
+                (false, false) => node.nw,
+                (true, false) => node.ne,
+                (false, true) => node.sw,
+                (true, true) => node.se,
+            };
+            x %= half;
+            y %= half;
+        }
+        id == ALIVE
+    }
+
+    pub fn set_cell(&mut self, x: i64, y: i64, alive: bool) -> Result<(), String> {
+        while !self.contains(x, y) {
+            if self.level(self.root) >= MAX_LEVEL {
+                return Err(format!("Cell ({}, {}) is outside the universe", x, y));
+            }
+            self.expand();
+        }
+
+        let half = self.half();
+        self.root = self.set_in(self.root, x + half, y + half, alive);
+        Ok(())
+    }
+
+    fn set_in(&mut self, id: NodeId, x: i64, y: i64, alive: bool) -> NodeId {
+        let node = self.node(id);
+        if node.level == 0 {
+            return if alive { ALIVE } else { DEAD };
+        }
+
+        let half = 1 << (node.level - 1);
+        let (mut nw, mut ne, mut sw, mut se) = (node.nw, node.ne, node.sw, node.se);
+        match (x >= half, y >= half) {
+            (false, false) => nw = self.set_in(nw, x, y, alive),
+            (true, false) => ne = self.set_in(ne, x - half, y, alive),
+            (false, true) => sw = self.set_in(sw, x, y - half, alive),
+            (true, true) => se = self.set_in(se, x - half, y - half, alive),
+        }
+        self.join(nw, ne, sw, se)
+    }
+
+    // Adds the pattern's live cells with its (0, 0) at (x, y).
+    pub fn add_pattern(&mut self, pattern: &Pattern, x: i64, y: i64) -> Result<(), String> {
+        for &(dx, dy) in &pattern.cells {
+            self.set_cell(x + dx as i64, y + dy as i64, true)?;
+        }
+        Ok(())
+    }
+
+    // The live cells at their universe coordinates.
+    pub fn to_pattern(&self) -> Result<Pattern, String> {
+        let cells = self
+            .cells()
+            .into_iter()
+            .map(|(x, y)| Ok((i32::try_from(x)?, i32::try_from(y)?)))
+            .collect::<Result<Vec<_>, std::num::TryFromIntError>>()
+            .map_err(|_| "Pattern is too large to convert to a cell list".to_string())?;
+        Ok(Pattern::new("", cells))
+    }
+
+    // Inclusive bounds of the live cells, or None when the universe is empty.
+    pub fn bounds(&self) -> Option<(i64, i64, i64, i64)> {
+        if self.population() == 0 {
+            return None;
+        }
+
+        let half = self.half();
+        let mut bounds: Option<(i64, i64, i64, i64)> = None;
+        self.visit(self.root, -half, -half, &mut |x, y| {
+            bounds = Some(match bounds {
+                Some((min_x, min_y, max_x, max_y)) => {
+                    (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+                }
+                None => (x, y, x, y),
+            });
+        });
+        bounds
+    }
+
+    // Live cells in no particular order.
+    pub fn cells(&self) -> Vec<(i64, i64)> {
+        let half = self.half();
+        let mut cells = Vec::with_capacity(self.population().min(1 << 24) as usize);
+        self.visit(self.root, -half, -half, &mut |x, y| cells.push((x, y)));
+        cells
+    }
+
+    fn visit(&self, id: NodeId, x: i64, y: i64, f: &mut impl FnMut(i64, i64)) {
+        let entry = &self.nodes[id as usize];
+        if entry.population == 0 {
+            return;
+        }
+        let node = entry.node;
+        if node.level == 0 {
+            f(x, y);
+            return;
+        }
+
+        let half = 1 << (node.level - 1);
+        self.visit(node.nw, x, y, f);
+        self.visit(node.ne, x + half, y, f);
+        self.visit(node.sw, x, y + half, f);
+        self.visit(node.se, x + half, y + half, f);
+    }
+
+    // One byte per cell for the rectangle at (x, y), row by row.
+    pub fn read_region(&self, x: i64, y: i64, width: u32, height: u32) -> Vec<u8> {
+        let mut region = vec![0u8; (width * height) as usize];
+        let half = self.half();
+        self.fill(
+            self.root,
+            -half,
+            -half,
+            (x, y, width as i64, height as i64),
+            &mut region,
+        );
+        region
+    }
+
+    fn fill(
+        &self,
+        id: NodeId,
+        node_x: i64,
+        node_y: i64,
+        region: (i64, i64, i64, i64),
+        output: &mut [u8],
+    ) {
+        let entry = &self.nodes[id as usize];
+        let (x, y, width, height) = region;
+        let size = 1i64 << entry.node.level;
+        if entry.population == 0
+            || node_x >= x + width
+            || node_y >= y + height
+            || node_x + size <= x
+            || node_y + size <= y
+        {
+            return;
+        }
+
+        let node = entry.node;
+        if node.level == 0 {
+            output[((node_y - y) * width + (node_x - x)) as usize] = 1;
+            return;
+        }
+
+        let half = size / 2;
+        self.fill(node.nw, node_x, node_y, region, output);
+        self.fill(node.ne, node_x + half, node_y, region, output);
+        self.fill(node.sw, node_x, node_y + half, region, output);
+        self.fill(node.se, node_x + half, node_y + half, region, output);
+    }
+
+    // Advances the universe by 2^k generations.
+    pub fn step_pow2(&mut self, k: u32) -> Result<(), String> {
+        if k > MAX_STEP_POW2 {
+            return Err(format!("Cannot step 2^{} generations at once", k));
+        }
+        if self.rule.birth & 1 != 0 {
+            return Err("HashLife does not support rules with B0".to_string());
+        }
+        if self.nodes.len() > GARBAGE_LIMIT {
+            self.collect_garbage();
+        }
+
+        // The pattern has to sit in the central quarter of the root so that
+        // neither it nor anything it emits can leave the result square.
+        while self.level(self.root) < k + 3 || !self.centred() {
+            if self.level(self.root) >= MAX_LEVEL {
+                return Err("Pattern has outgrown the universe".to_string());
+            }
+            self.expand();
+        }
+
+        self.root = self.advance(self.root, k);
+        while self.level(self.root) < MIN_ROOT_LEVEL {
+            self.expand();
+        }
+        self.generation += 1 << k;
+        Ok(())
+    }
+
+    // Advances any number of generations as a series of power-of-two jumps.
+    pub fn step(&mut self, generations: u64) -> Result<(), String> {
+        for k in 0..u64::BITS {
+            if generations & (1 << k) != 0 {
+                self.step_pow2(k)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn centred(&mut self) -> bool {
+        let inner = self.centre(self.root);
+        let inner = self.centre(inner);
+        self.nodes[inner as usize].population == self.population()
+    }
+
+    fn centre(&mut self, id: NodeId) -> NodeId {
+        let node = self.node(id);
+        let (nw, ne, sw, se) = (
+            self.node(node.nw),
+            self.node(node.ne),
+            self.node(node.sw),
+            self.node(node.se),
+        );
+        self.join(nw.se, ne.sw, sw.ne, se.nw)
+    }
+
+    // The centre half of a level-n node advanced by 2^k generations, for
+    // k <= n - 2.
+    fn advance(&mut self, id: NodeId, k: u32) -> NodeId {
+        let node = self.node(id);
+        if self.nodes[id as usize].population == 0 {
+            return self.empty(node.level - 1);
+        }
+        if let Some(&result) = self.results.get(&(id, k)) {
+            return result;
+        }
+
+        let result = if node.level == 2 {
+            self.advance_leaf(node)
+        } else {
+            let (nw, ne, sw, se) = (
+                self.node(node.nw),
+                self.node(node.ne),
+                self.node(node.sw),
+                self.node(node.se),
+            );
+            let parts = [
+                node.nw,
+                self.join(nw.ne, ne.nw, nw.se, ne.sw),
+                node.ne,
+                self.join(nw.sw, nw.se, sw.nw, sw.ne),
+                self.join(nw.se, ne.sw, sw.ne, se.nw),
+                self.join(ne.sw, ne.se, se.nw, se.ne),
+                node.sw,
+                self.join(sw.ne, se.nw, sw.se, se.sw),
+                node.se,
+            ];
+
+            // A full-size jump advances the nine parts by half the distance
+            // and then the four overlapping squares by the other half; smaller
+            // jumps only advance the four squares.
+            let full = k == node.level - 2;
+            let mut inner = [DEAD; 9];
+            for (part, &id) in inner.iter_mut().zip(&parts) {
+                *part = if full {
+                    self.advance(id, k - 1)
+                } else {
+                    self.centre(id)
+                };
+            }
+            let k = if full { k - 1 } else { k };
+
+            let quads = [
+                self.join(inner[0], inner[1], inner[3], inner[4]),
+                self.join(inner[1], inner[2], inner[4], inner[5]),
+                self.join(inner[3], inner[4], inner[6], inner[7]),
+                self.join(inner[4], inner[5], inner[7], inner[8]),
+            ];
+            let nw = self.advance(quads[0], k);
+            let ne = self.advance(quads[1], k);
+            let sw = self.advance(quads[2], k);
+            let se = self.advance(quads[3], k);
+            self.join(nw, ne, sw, se)
+        };
+
+        self.results.insert((id, k), result);
+        result
+    }
+
+    // Runs one generation on the 4x4 block of a level-2 node.
+    fn advance_leaf(&mut self, node: Node) -> NodeId {
+        let mut cells = [[false; 4]; 4];
+        for (index, &quadrant) in [node.nw, node.ne, node.sw, node.se].iter().enumerate() {
+            let quadrant = self.node(quadrant);
+            let (x, y) = ((index % 2) * 2, (index / 2) * 2);
+            cells[y][x] = quadrant.nw == ALIVE;
+            cells[y][x + 1] = quadrant.ne == ALIVE;
+            cells[y + 1][x] = quadrant.sw == ALIVE;
+            cells[y + 1][x + 1] = quadrant.se == ALIVE;
+        }
+
+        let mut next = [DEAD; 4];
+        for (index, cell) in next.iter_mut().enumerate() {
+            let (x, y) = (1 + index % 2, 1 + index / 2);
+            let neighbors = cells[y - 1..=y + 1]
+                .iter()
+                .flat_map(|row| &row[x - 1..=x + 1])
+                .filter(|&&alive| alive)
+                .count() as u8
+                - cells[y][x] as u8;
+            if self.rule.next_state(cells[y][x], neighbors) {
+                *cell = ALIVE;
+            }
+        }
+        self.join(next[0], next[1], next[2], next[3])
+    }
+
+    // Rebuilds the arena with only the nodes reachable from the root.
+    fn collect_garbage(&mut self) {
+        let old = std::mem::take(&mut self.nodes);
+        let root = self.root;
+        self.reset();
+
+        let mut copied = HashMap::new();
+        copied.insert(DEAD, DEAD);
+        copied.insert(ALIVE, ALIVE);
+        self.root = self.copy_node(&old, root, &mut copied);
+    }
+
+    fn copy_node(
+        &mut self,
+        old: &[Entry],
+        id: NodeId,
+        copied: &mut HashMap<NodeId, NodeId>,
+    ) -> NodeId {
+        if let Some(&id) = copied.get(&id) {
+            return id;
+        }
+
+        let node = old[id as usize].node;
+        let nw = self.copy_node(old, node.nw, copied);
+        let ne = self.copy_node(old, node.ne, copied);
+        let sw = self.copy_node(old, node.sw, copied);
+        let se = self.copy_node(old, node.se, copied);
+        let new_id = self.join(nw, ne, sw, se);
+        copied.insert(id, new_id);
+        new_id
+    }
+}
+
+impl Default for Universe {
+    fn default() -> Self {
+        Universe::new()
+    }
+}
+
+// Shows the [0, width) x [0, height) window of an unbounded universe. The
+// topology is ignored: HashLife always runs on an infinite plane.
+pub struct HashLifeSimulator {
+    universe: Universe,
+    // The window as of the current and the previous generation.
+    cells: Vec<u8>,
+    previous: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+impl HashLifeSimulator {
+    pub fn new(width: u32, height: u32) -> Self {
+        let size = (width * height) as usize;
+        Self {
+            universe: Universe::new(),
+            cells: vec![0; size],
+            previous: vec![0; size],
+            width,
+            height,
+        }
+    }
+
+    fn refresh(&mut self) {
+        self.cells = self.universe.read_region(0, 0, self.width, self.height);
+    }
+}
+
+impl Simulator for HashLifeSimulator {
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn load(&mut self, cells: &[u8]) -> Result<(), JsValue> {
+        let rule = self.universe.rule();
+        self.universe.clear();
+        self.universe.set_rule(rule);
+
+        for (index, &value) in cells.iter().enumerate() {
+            if value != 0 {
+                let x = (index as u32 % self.width) as i64;
+                let y = (index as u32 / self.width) as i64;
+                self.universe
+                    .set_cell(x, y, true)
+                    .map_err(|e| JsValue::from_str(&e))?;
+            }
+        }
+
+        self.refresh();
+        self.previous.copy_from_slice(&self.cells);
+        Ok(())
+    }
+
+    fn set_cell(&mut self, x: u32, y: u32, alive: bool) -> Result<(), JsValue> {
+        if x < self.width && y < self.height {
+            self.universe
+                .set_cell(x as i64, y as i64, alive)
+                .map_err(|e| JsValue::from_str(&e))?;
+            self.cells[(y * self.width + x) as usize] = alive as u8;
+        }
+        Ok(())
+    }
+
+    fn read_region(&self, x: u32, y: u32, width: u32, height: u32) -> Result<Vec<u8>, JsValue> {
+        let mut region = Vec::with_capacity((width * height) as usize);
+        for row in y..y + height {
+            let start = (row * self.width + x) as usize;
+            region.extend_from_slice(&self.cells[start..start + width as usize]);
+        }
+        Ok(region)
+    }
+
+    fn statistics(&self) -> Result<Statistics, JsValue> {
+        Ok(Statistics::from_cells(
+            &self.previous,
+            &self.cells,
+            self.width,
+            self.height,
+        ))
+    }
+
+    fn set_rule(&mut self, rule: Rule) {
+        self.universe.set_rule(rule);
+    }
+
+    fn set_topology(&mut self, _topology: Topology) {}
+
+    fn step(&mut self) -> Result<(), JsValue> {
+        self.step_pow2(0)
+    }
+
+    fn step_n(&mut self, generations: u32) -> Result<(), JsValue> {
+        self.universe
+            .step(generations as u64)
+            .map_err(|e| JsValue::from_str(&e))?;
+        std::mem::swap(&mut self.previous, &mut self.cells);
+        self.refresh();
+        Ok(())
+    }
+
+    fn max_step_pow2(&self) -> u32 {
+        MAX_STEP_POW2
+    }
+
+    fn step_pow2(&mut self, k: u32) -> Result<(), JsValue> {
+        self.universe
+            .step_pow2(k)
+            .map_err(|e| JsValue::from_str(&e))?;
+        std::mem::swap(&mut self.previous, &mut self.cells);
+        self.refresh();
+        Ok(())
+    }
+
+    fn host_cells(&self) -> Option<&[u8]> {
+        Some(&self.cells)
+    }
+
+    fn universe(&self) -> Option<&Universe> {
+        Some(&self.universe)
+    }
+
+    fn set_universe(&mut self, mut universe: Universe) -> Result<(), JsValue> {
+        universe.set_rule(self.universe.rule());
+        self.universe = universe;
+        self.refresh();
+        self.previous.copy_from_slice(&self.cells);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tree_round_trip_keeps_distant_cells_and_generation() {
+        let mut universe = Universe::new();
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            universe.set_cell(x, y, true).unwrap();
+        }
+        // A block far outside any grid window.
+        for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            universe
+                .set_cell(x - 1_000_000, y + 5_000_000, true)
+                .unwrap();
+        }
+        universe.step(100).unwrap();
+        universe.collect_garbage();
+
+        let tree = universe.to_tree();
+        let copy = Universe::from_tree(&tree, universe.rule());
+        assert_eq!(copy.generation(), 100);
+        assert_eq!(copy.population(), 9);
+        let mut cells = copy.cells();
+        let mut expected = universe.cells();
+        cells.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(cells, expected);
+    }
+
+    #[test]
+    fn oversized_jumps_are_rejected() {
+        let mut universe = Universe::new();
+        universe.set_cell(0, 0, true).unwrap();
+        assert!(universe.step_pow2(MAX_STEP_POW2 + 1).is_err());
+        assert!(universe.step_pow2(64).is_err());
+        assert!(universe.step_pow2(u32::MAX).is_err());
+        assert_eq!(universe.generation(), 0);
+    }
+}
@@ -0,0 +1,348 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::snapshot::Snapshot;
+
+pub const DEFAULT_BUDGET: usize = 64 * 1024 * 1024;
+
+// Entries are swaps: applying one writes its contents into the grid and keeps
+// what was there instead, so the same entry serves for undo and redo.
+pub enum Entry {
+    // (cell index, value) pairs for individual edits.
+    Cells(Vec<(u32, u8)>),
+    // The whole grid, for bulk edits and runs of steps.
+    Grid(Snapshot),
+}
+
+impl Entry {
+    fn size(&self) -> usize {
+        match self {
+            Entry::Cells(cells) => cells.len() * std::mem::size_of::<(u32, u8)>(),
+            Entry::Grid(snapshot) => snapshot.size(),
+        }
+    }
+}
+
+// Edits made while a group is open become one entry, so a brush stroke or a
+// line is undone in one go.
+#[derive(Default)]
+struct Group {
+    cells: Vec<(u32, u8)>,
+    seen: HashSet<u32>,
+}
+
+pub struct History {
+    undo: VecDeque<Entry>,
+    redo: Vec<Entry>,
+    group: Option<Group>,
+    depth: u32,
+    // Set while consecutive steps share the snapshot taken before the first.
+    stepping: bool,
+    budget: usize,
+    size: usize,
+}
+
+impl History {
+    pub fn new() -> Self {
+        History {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            group: None,
+            depth: 0,
+            stepping: false,
+            budget: DEFAULT_BUDGET,
+            size: 0,
+        }
+    }
+
+    pub fn begin_group(&mut self) {
+        self.depth += 1;
+        self.group.get_or_insert_with(Group::default);
+    }
+
+    pub fn end_group(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+        if self.depth == 0 {
+            self.commit_group();
+        }
+    }
+
+    // Commits the open group; a group still being recorded carries on empty.
+    fn commit_group(&mut self) {
+        if let Some(group) = self.group.take()
+            && !group.cells.is_empty()
+        {
+            self.push(Entry::Cells(group.cells));
+        }
+        if self.depth > 0 {
+            self.group = Some(Group::default());
+        }
+    }
+
+    // Records the value a cell had before an edit.
+    pub fn record_cell(&mut self, index: u32, before: u8) {
+        match self.group.as_mut() {
+            Some(group) => {
+                if group.seen.insert(index) {
+                    group.cells.push((index, before));
+                    self.clear_redo();
+                    self.stepping = false;
+                }
+            }
+            None => self.push(Entry::Cells(vec![(index, before)])),
+        }
+    }
+
+    // Records the grid before a bulk edit.
+    pub fn record_grid(&mut self, before: Snapshot) {
+        self.commit_group();
+        self.push(Entry::Grid(before));
+    }
+
+    // Only the first of a run of steps needs the grid recorded; undoing it
+    // rewinds the whole run.
+    pub fn needs_step_snapshot(&self) -> bool {
+        !self.stepping
+    }
+
+    pub fn record_steps(&mut self, before: Snapshot) {
+        self.record_grid(before);
+        self.stepping = true;
+    }
+
+    fn clear_redo(&mut self) {
+        for entry in self.redo.drain(..) {
+            self.size -= entry.size();
+        }
+    }
+
+    fn push(&mut self, entry: Entry) {
+        self.clear_redo();
+        self.size += entry.size();
+        self.undo.push_back(entry);
+        self.stepping = false;
+        self.enforce_budget();
+    }
+
+    pub fn pop_undo(&mut self) -> Option<Entry> {
+        self.commit_group();
+        self.stepping = false;
+        let entry = self.undo.pop_back()?;
+        self.size -= entry.size();
+        Some(entry)
+    }
+
+    pub fn pop_redo(&mut self) -> Option<Entry> {
+        self.commit_group();
+        self.stepping = false;
+        let entry = self.redo.pop()?;
+        self.size -= entry.size();
+        Some(entry)
+    }
+
+    pub fn push_undo(&mut self, entry: Entry) {
+        self.size += entry.size();
+        self.undo.push_back(entry);
+        self.enforce_budget();
+    }
+
+    pub fn push_redo(&mut self, entry: Entry) {
+        self.size += entry.size();
+        self.redo.push(entry);
+        self.enforce_budget();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+            || self
+                .group
+                .as_ref()
+                .is_some_and(|group| !group.cells.is_empty())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.enforce_budget();
+    }
+
+    // Bytes held by the recorded entries.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.group = None;
+        self.depth = 0;
+        self.stepping = false;
+        self.size = 0;
+    }
+
+    // Forgets the oldest undo entries first, then the furthest redo entries.
+    fn enforce_budget(&mut self) {
+        while self.size > self.budget {
+            let entry = match self.undo.pop_front() {
+                Some(entry) => entry,
+                None if !self.redo.is_empty() => self.redo.remove(0),
+                None => break,
+            };
+            self.size -= entry.size();
+        }
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Swaps an entry into the grid the way `GameOfLife` does.
+    fn apply(grid: &mut Vec<u8>, entry: Entry) -> Entry {
+        match entry {
+            Entry::Cells(cells) => Entry::Cells(
+                cells
+                    .into_iter()
+                    .map(|(index, value)| {
+                        (index, std::mem::replace(&mut grid[index as usize], value))
+                    })
+                    .collect(),
+            ),
+            Entry::Grid(snapshot) => {
+                let replaced = Snapshot::from_cells(grid);
+                *grid = snapshot.to_cells();
+                Entry::Grid(replaced)
+            }
+        }
+    }
+
+    fn edit(history: &mut History, grid: &mut [u8], index: u32, value: u8) {
+        history.record_cell(index, grid[index as usize]);
+        grid[index as usize] = value;
+    }
+
+    fn undo(history: &mut History, grid: &mut Vec<u8>) -> bool {
+        let Some(entry) = history.pop_undo() else {
+            return false;
+        };
+        let entry = apply(grid, entry);
+        history.push_redo(entry);
+        true
+    }
+
+    fn redo(history: &mut History, grid: &mut Vec<u8>) -> bool {
+        let Some(entry) = history.pop_redo() else {
+            return false;
+        };
+        let entry = apply(grid, entry);
+        history.push_undo(entry);
+        true
+    }
+
+    #[test]
+    fn undo_and_redo_swap_the_same_entry() {
+        let mut history = History::new();
+        let mut grid = vec![0u8; 8];
+        edit(&mut history, &mut grid, 3, 1);
+        edit(&mut history, &mut grid, 5, 1);
+
+        assert!(undo(&mut history, &mut grid));
+        assert_eq!(grid, [0, 0, 0, 1, 0, 0, 0, 0]);
+        assert!(undo(&mut history, &mut grid));
+        assert_eq!(grid, [0; 8]);
+        assert!(!undo(&mut history, &mut grid));
+
+        assert!(redo(&mut history, &mut grid));
+        assert!(redo(&mut history, &mut grid));
+        assert_eq!(grid, [0, 0, 0, 1, 0, 1, 0, 0]);
+        assert!(!history.can_redo());
+
+        // Bulk changes are undone through a snapshot of the whole grid.
+        history.record_grid(Snapshot::from_cells(&grid));
+        grid = vec![1; 8];
+        assert!(undo(&mut history, &mut grid));
+        assert_eq!(grid, [0, 0, 0, 1, 0, 1, 0, 0]);
+        assert!(redo(&mut history, &mut grid));
+        assert_eq!(grid, [1; 8]);
+    }
+
+    #[test]
+    fn edits_in_a_group_undo_together() {
+        let mut history = History::new();
+        let mut grid = vec![0u8; 8];
+        history.begin_group();
+        edit(&mut history, &mut grid, 1, 1);
+        history.begin_group();
+        edit(&mut history, &mut grid, 2, 1);
+        history.end_group();
+        // Only the value from before the group's first edit is kept.
+        edit(&mut history, &mut grid, 1, 0);
+        edit(&mut history, &mut grid, 1, 1);
+        assert!(history.can_undo());
+        history.end_group();
+
+        assert!(undo(&mut history, &mut grid));
+        assert_eq!(grid, [0; 8]);
+        assert!(!history.can_undo());
+
+        // A run of steps shares the snapshot taken before the first.
+        history.record_steps(Snapshot::from_cells(&grid));
+        assert!(!history.needs_step_snapshot());
+        edit(&mut history, &mut grid, 0, 1);
+        assert!(history.needs_step_snapshot());
+    }
+
+    #[test]
+    fn new_edits_clear_redo() {
+        let mut history = History::new();
+        let mut grid = vec![0u8; 8];
+        edit(&mut history, &mut grid, 0, 1);
+        edit(&mut history, &mut grid, 1, 1);
+        assert!(undo(&mut history, &mut grid));
+        assert!(history.can_redo());
+        let size = history.size();
+
+        edit(&mut history, &mut grid, 7, 1);
+        assert!(!history.can_redo());
+        assert!(!redo(&mut history, &mut grid));
+        assert_eq!(history.size(), size);
+        assert_eq!(grid, [1, 0, 0, 0, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn the_budget_forgets_the_oldest_entries() {
+        let entry = std::mem::size_of::<(u32, u8)>();
+        let mut history = History::new();
+        history.set_budget(2 * entry);
+        let mut grid = vec![0u8; 8];
+        for index in 0..4 {
+            edit(&mut history, &mut grid, index, 1);
+        }
+        assert_eq!(history.size(), 2 * entry);
+
+        assert!(undo(&mut history, &mut grid));
+        assert!(undo(&mut history, &mut grid));
+        assert!(!undo(&mut history, &mut grid));
+        assert_eq!(grid, [1, 1, 0, 0, 0, 0, 0, 0]);
+
+        // Once undo is empty, shrinking the budget drops the redo entry that
+        // would be redone last.
+        history.set_budget(entry);
+        assert_eq!(history.size(), entry);
+        assert!(redo(&mut history, &mut grid));
+        assert_eq!(grid, [1, 1, 1, 0, 0, 0, 0, 0]);
+        assert!(!history.can_redo());
+    }
+}
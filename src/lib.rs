@@ -1,24 +1,79 @@
-use wasm_bindgen::JsCast;
 use wasm_bindgen::prelude::*;
-use web_sys::{WebGlBuffer, WebGlFramebuffer, WebGlProgram, WebGlRenderingContext, WebGlTexture};
+use web_sys::{HtmlCanvasElement, OffscreenCanvas, WebGlRenderingContext};
 
-mod patterns;
+pub mod apgcode;
+pub mod camera;
+pub mod catalog;
+pub mod census;
+pub mod color;
+pub mod cpu;
+mod gpu;
+pub mod hashlife;
+pub mod history;
+pub mod life106;
+pub mod macrocell;
+pub mod patterns;
+pub mod period;
+pub mod plaintext;
+pub mod registry;
+mod renderer;
+pub mod rle;
+pub mod rule;
 mod shaders;
+pub mod simulator;
+pub mod snapshot;
+pub mod soup;
+pub mod stats;
 mod texture;
+pub mod timeline;
+pub mod topology;
 mod webgl;
 
-use patterns::Pattern;
+use camera::Camera;
+use census::CensusEntry;
+use color::{Appearance, Color, ColorMode, Gradient, Palette, Theme};
+use cpu::CpuSimulator;
+use gpu::GpuSimulator;
+use hashlife::{HashLifeSimulator, Universe};
+use history::{Entry, History};
+use patterns::{Pattern, PlaceMode, Transform};
+use period::{PeriodDetector, Periodicity};
+use registry::{PatternInfo, Registry};
+use renderer::View;
+use rule::Rule;
+use simulator::{Backend, Simulator};
+use snapshot::Snapshot;
+use soup::Symmetry;
+use stats::Statistics;
 use texture::TextureManager;
+use timeline::Timeline;
+use topology::Topology;
+use webgl::RenderTarget;
 
 #[wasm_bindgen]
 pub struct GameOfLife {
-    gl: WebGlRenderingContext,
-    compute_program: WebGlProgram,
-    render_program: WebGlProgram,
-    current_state: WebGlTexture,
-    next_state: WebGlTexture,
-    quad_buffer: WebGlBuffer,
-    framebuffer: WebGlFramebuffer,
+    // The view the engine was created with; the simulator shares its context.
+    primary: View,
+    // Views attached later, indexed by id - 1. Detached views leave a hole so
+    // ids stay stable.
+    views: Vec<Option<View>>,
+    // Bumped on every change to the cells so views know when to re-upload.
+    state_version: u64,
+    // CPU-side copy of the grid so edits can read cells without a GPU round
+    // trip; dropped whenever the simulation advances and rebuilt on demand.
+    shadow: Option<Vec<u8>>,
+    simulator: Box<dyn Simulator>,
+    history: History,
+    patterns: Registry,
+    timeline: Timeline,
+    // Set while period detection is on, with the state version it last saw;
+    // any other change since then restarts the detection.
+    periods: Option<PeriodDetector>,
+    periods_version: u64,
+    appearance: Appearance,
+    backend: Backend,
+    rule: Rule,
+    topology: Topology,
     texture_manager: TextureManager,
     width: u32,
     height: u32,
@@ -28,30 +83,71 @@ pub struct GameOfLife {
 impl GameOfLife {
     #[wasm_bindgen(constructor)]
     pub fn new(canvas_id: &str, width: u32, height: u32) -> Result<GameOfLife, JsValue> {
-        let gl = webgl::init_webgl_context(canvas_id)?;
+        Self::with_backend(canvas_id, width, height, Backend::WebGl)
+    }
 
-        let current_state = webgl::create_texture(&gl, width, height)?;
-        let next_state = webgl::create_texture(&gl, width, height)?;
+    // Every backend draws through WebGL, so the Cpu and HashLife backends
+    // need a canvas too; without a browser, drive `cpu::CpuSimulator` or
+    // `hashlife::Universe` directly.
+    #[wasm_bindgen]
+    pub fn with_backend(
+        canvas_id: &str,
+        width: u32,
+        height: u32,
+        backend: Backend,
+    ) -> Result<GameOfLife, JsValue> {
+        Self::with_target(RenderTarget::from_id(canvas_id)?, width, height, backend)
+    }
 
-        let framebuffer = gl
-            .create_framebuffer()
-            .ok_or_else(|| JsValue::from_str("Failed to create framebuffer"))?;
+    #[wasm_bindgen]
+    pub fn from_canvas(
+        canvas: HtmlCanvasElement,
+        width: u32,
+        height: u32,
+        backend: Backend,
+    ) -> Result<GameOfLife, JsValue> {
+        Self::with_target(RenderTarget::Canvas(canvas), width, height, backend)
+    }
 
-        let quad_buffer = webgl::create_quad_buffer(&gl)?;
+    #[wasm_bindgen]
+    pub fn from_offscreen_canvas(
+        canvas: OffscreenCanvas,
+        width: u32,
+        height: u32,
+        backend: Backend,
+    ) -> Result<GameOfLife, JsValue> {
+        Self::with_target(RenderTarget::Offscreen(canvas), width, height, backend)
+    }
+
+    fn with_target(
+        target: RenderTarget,
+        width: u32,
+        height: u32,
+        backend: Backend,
+    ) -> Result<GameOfLife, JsValue> {
+        let gl = webgl::init_webgl_context(&target)?;
 
-        let compute_program = shaders::create_compute_program(&gl)?;
-        let render_program = shaders::create_render_program(&gl)?;
+        let simulator = create_simulator(&gl, backend, width, height)?;
 
         let texture_manager = TextureManager::new(gl.clone(), width, height);
 
+        let primary = View::new(target, gl, width, height)?;
+
         let game = GameOfLife {
-            gl,
-            compute_program,
-            render_program,
-            current_state,
-            next_state,
-            quad_buffer,
-            framebuffer,
+            primary,
+            views: Vec::new(),
+            state_version: 0,
+            shadow: None,
+            simulator,
+            history: History::new(),
+            patterns: Registry::builtin(),
+            timeline: Timeline::default(),
+            periods: None,
+            periods_version: 0,
+            appearance: Appearance::default(),
+            backend,
+            rule: Rule::default(),
+            topology: Topology::default(),
             texture_manager,
             width,
             height,
@@ -61,10 +157,113 @@ impl GameOfLife {
     }
 
     #[wasm_bindgen]
-    pub fn randomize(&mut self) -> Result<(), JsValue> {
-        let data = self.texture_manager.create_random_data(0.3);
-        self.upload_texture_data(data)?;
-        Ok(())
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    #[wasm_bindgen]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // Accepts an optional Golly topology suffix, e.g. "B3/S23:P200,100".
+    #[wasm_bindgen]
+    pub fn set_rule(&mut self, rule: &str) -> Result<(), JsValue> {
+        let (rule, topology) = match rule.split_once(':') {
+            Some((rule, topology)) => (rule, Some(topology)),
+            None => (rule, None),
+        };
+
+        let rule = Rule::parse(rule).map_err(|e| JsValue::from_str(&e))?;
+        if self.backend == Backend::HashLife && rule.birth & 1 != 0 {
+            return Err(JsValue::from_str("HashLife does not support rules with B0"));
+        }
+        if let Some(topology) = topology {
+            self.set_topology(topology)?;
+        }
+
+        self.rule = rule;
+        self.simulator.set_rule(rule);
+        self.timeline.clear_future();
+        self.restart_period_detection()
+    }
+
+    // The HashLife backend runs on an unbounded plane, so it has no suffix.
+    #[wasm_bindgen]
+    pub fn rule(&self) -> String {
+        if self.backend == Backend::HashLife {
+            return self.rule.to_string();
+        }
+        format!(
+            "{}:{}",
+            self.rule,
+            self.topology.suffix(self.width, self.height)
+        )
+    }
+
+    // Takes a Golly-style suffix such as "T200,100", "P", "K200*,100" or
+    // "C200,100". Giving a size different from the current grid resizes it.
+    #[wasm_bindgen]
+    pub fn set_topology(&mut self, topology: &str) -> Result<(), JsValue> {
+        let (topology, size) = Topology::parse(topology).map_err(|e| JsValue::from_str(&e))?;
+
+        if let Some((width, height)) = size
+            && (width, height) != (self.width, self.height)
+        {
+            self.resize(width, height)?;
+        }
+
+        self.topology = topology;
+        self.simulator.set_topology(topology);
+        self.timeline.clear_future();
+        self.restart_period_detection()
+    }
+
+    // Fills the grid with a 30% soup from a fresh seed and returns the seed,
+    // so the soup can be reproduced with `randomize_with`.
+    #[wasm_bindgen]
+    pub fn randomize(&mut self) -> Result<u32, JsValue> {
+        let seed = (js_sys::Math::random() * u32::MAX as f64) as u32;
+        self.randomize_with(seed, 0.3, 0, 0, self.width, self.height, Symmetry::C1)?;
+        Ok(seed)
+    }
+
+    // Clears the grid and fills the region at (x, y) with a soup that depends
+    // only on the seed, density, region size and symmetry.
+    #[wasm_bindgen]
+    #[allow(clippy::too_many_arguments)]
+    pub fn randomize_with(
+        &mut self,
+        seed: u32,
+        density: f64,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        symmetry: Symmetry,
+    ) -> Result<(), JsValue> {
+        if x.saturating_add(width) > self.width || y.saturating_add(height) > self.height {
+            return Err(JsValue::from_str(&format!(
+                "Region {}x{} at ({}, {}) is outside the {}x{} grid",
+                width, height, x, y, self.width, self.height
+            )));
+        }
+
+        let soup = soup::generate(seed as u64, density, width, height, symmetry)
+            .map_err(|e| JsValue::from_str(&e))?;
+        let mut cells = vec![0u8; (self.width * self.height) as usize];
+        for (row, soup_row) in soup.chunks(width.max(1) as usize).enumerate() {
+            let start = ((y + row as u32) * self.width + x) as usize;
+            cells[start..start + soup_row.len()].copy_from_slice(soup_row);
+        }
+
+        self.record_grid()?;
+        self.load_cells(cells)
     }
 
     #[wasm_bindgen]
@@ -75,197 +274,745 @@ impl GameOfLife {
     }
 
     #[wasm_bindgen]
-    pub fn load_glider(&mut self) -> Result<(), JsValue> {
-        let pattern = Pattern::glider();
-        let positions = pattern.scaled_for_grid(self.width, self.height);
-        let data = self.texture_manager.create_pattern_data(&positions);
-        self.upload_texture_data(data)?;
+    pub fn list_patterns(&self) -> Vec<PatternInfo> {
+        self.patterns.infos()
+    }
+
+    // Clears the grid and centres the named pattern on it, switching to the
+    // pattern's rule if it was registered with one.
+    #[wasm_bindgen]
+    pub fn load_pattern(&mut self, name: &str) -> Result<(), JsValue> {
+        let entry = self
+            .patterns
+            .get(name)
+            .cloned()
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown pattern \"{}\"", name)))?;
+        if let Some(rule) = &entry.rule {
+            self.set_rule(rule)?;
+        }
+
+        let pattern = entry.pattern;
+        let x = (self.width as i32 - pattern.width() as i32) / 2;
+        let y = (self.height as i32 - pattern.height() as i32) / 2;
+        self.load_pattern_at(pattern, x, y)
+    }
+
+    // For `place_pattern`.
+    #[wasm_bindgen]
+    pub fn get_pattern(&self, name: &str) -> Result<Pattern, JsValue> {
+        self.patterns
+            .get(name)
+            .map(|entry| entry.pattern.clone())
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown pattern \"{}\"", name)))
+    }
+
+    // Adds an RLE pattern to the registry, replacing one with the same name.
+    // An empty name uses the pattern's #N line.
+    #[wasm_bindgen]
+    pub fn register_pattern(
+        &mut self,
+        name: &str,
+        category: &str,
+        rle: &str,
+    ) -> Result<(), JsValue> {
+        self.patterns
+            .register_rle(name, category, rle)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    #[wasm_bindgen]
+    pub fn unregister_pattern(&mut self, name: &str) -> bool {
+        self.patterns.remove(name).is_some()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_cell(&self, x: u32, y: u32) -> Result<bool, JsValue> {
+        if x >= self.width || y >= self.height {
+            return Ok(false);
+        }
+
+        let cell = self.simulator.read_region(x, y, 1, 1)?;
+        Ok(cell[0] != 0)
+    }
+
+    // Returns one byte per cell (0 = dead, 1 = alive), row by row from (x, y).
+    #[wasm_bindgen]
+    pub fn get_region(&self, x: u32, y: u32, width: u32, height: u32) -> Result<Vec<u8>, JsValue> {
+        if x.saturating_add(width) > self.width || y.saturating_add(height) > self.height {
+            return Err(JsValue::from_str(&format!(
+                "Region {}x{} at ({}, {}) is outside the {}x{} grid",
+                width, height, x, y, self.width, self.height
+            )));
+        }
+
+        self.simulator.read_region(x, y, width, height)
+    }
+
+    #[wasm_bindgen]
+    pub fn get_state(&self) -> Result<Vec<u8>, JsValue> {
+        self.simulator.read_cells()
+    }
+
+    #[wasm_bindgen]
+    pub fn statistics(&self) -> Result<Statistics, JsValue> {
+        self.simulator.statistics()
+    }
+
+    // Splits the current generation into objects and counts them by apgcode,
+    // using the current rule to classify them.
+    #[wasm_bindgen]
+    pub fn census(&mut self) -> Result<Vec<CensusEntry>, JsValue> {
+        let (width, rule) = (self.width, self.rule);
+        let objects = census::objects(self.shadow_cells()?, width, rule);
+        Ok(census::census(&objects))
+    }
+
+    // The census as CSV: apgcode, kind, period, count and cells per row.
+    #[wasm_bindgen]
+    pub fn export_census(&mut self) -> Result<String, JsValue> {
+        Ok(census::to_csv(&self.census()?))
+    }
+
+    #[wasm_bindgen]
+    pub fn load_rle(&mut self, text: &str, x: i32, y: i32) -> Result<(), JsValue> {
+        let (pattern, rule) = rle::parse(text).map_err(|e| JsValue::from_str(&e))?;
+        if let Some(rule) = rule {
+            self.set_rule(&rule)?;
+        }
+
+        self.load_pattern_at(pattern, x, y)
+    }
+
+    #[wasm_bindgen]
+    pub fn export_rle(&self) -> Result<String, JsValue> {
+        let pattern = self.current_pattern()?;
+        Ok(rle::write(&pattern, &self.rule()))
+    }
+
+    // Loads the object an apgcode describes with its bounding box at (x, y).
+    #[wasm_bindgen]
+    pub fn load_apgcode(&mut self, code: &str, x: i32, y: i32) -> Result<(), JsValue> {
+        let pattern = apgcode::decode(code).map_err(|e| JsValue::from_str(&e))?;
+        self.load_pattern_at(pattern, x, y)
+    }
+
+    // The canonical apgcode of everything on the grid taken as one object.
+    #[wasm_bindgen]
+    pub fn export_apgcode(&self) -> Result<String, JsValue> {
+        self.current_pattern()?.apgcode(&self.rule.to_string())
+    }
+
+    #[wasm_bindgen]
+    pub fn load_plaintext(&mut self, text: &str, x: i32, y: i32) -> Result<(), JsValue> {
+        let pattern = plaintext::parse(text).map_err(|e| JsValue::from_str(&e))?;
+        self.load_pattern_at(pattern, x, y)
+    }
+
+    #[wasm_bindgen]
+    pub fn export_plaintext(&self) -> Result<String, JsValue> {
+        let pattern = self.current_pattern()?;
+        Ok(plaintext::write(&pattern))
+    }
+
+    #[wasm_bindgen]
+    pub fn load_life106(&mut self, text: &str, x: i32, y: i32) -> Result<(), JsValue> {
+        let pattern = life106::parse(text).map_err(|e| JsValue::from_str(&e))?;
+        self.load_pattern_at(pattern, x, y)
+    }
+
+    #[wasm_bindgen]
+    pub fn export_life106(&self) -> Result<String, JsValue> {
+        let pattern = self.current_pattern()?;
+        Ok(life106::write(&pattern))
+    }
+
+    // Macrocell roots are centred on the origin, so the pattern is placed like
+    // the other formats, with its bottom-left corner at (x, y). The HashLife
+    // backend keeps the cells that fall outside the grid and the #G
+    // generation.
+    #[wasm_bindgen]
+    pub fn load_macrocell(&mut self, text: &str, x: i32, y: i32) -> Result<(), JsValue> {
+        let (universe, rule) = macrocell::parse(text).map_err(|e| JsValue::from_str(&e))?;
+        if let Some(rule) = rule {
+            self.set_rule(&rule)?;
+        }
+        let universe = macrocell::to_grid(&universe, x, y).map_err(|e| JsValue::from_str(&e))?;
+
+        self.record_grid()?;
+        self.simulator.set_universe(universe)?;
+        self.shadow = None;
+        self.state_version += 1;
         Ok(())
     }
 
     #[wasm_bindgen]
-    pub fn load_oscillator(&mut self) -> Result<(), JsValue> {
-        let pattern = Pattern::blinker();
-        let positions = pattern.scaled_for_grid(self.width, self.height);
+    pub fn export_macrocell(&self) -> Result<String, JsValue> {
+        let universe = match self.simulator.universe() {
+            Some(universe) => macrocell::from_grid(universe),
+            None => {
+                let mut universe = Universe::new();
+                universe
+                    .add_pattern(&self.current_pattern()?, 0, 0)
+                    .map(|_| universe)
+            }
+        }
+        .map_err(|e| JsValue::from_str(&e))?;
+        Ok(macrocell::write(&universe, &self.rule()))
+    }
+
+    // Places the transformed pattern with the bottom-left corner of its
+    // bounding box at (x, y), keeping the rest of the grid. Cells falling outside the grid are dropped.
+    #[wasm_bindgen]
+    pub fn place_pattern(
+        &mut self,
+        pattern: &Pattern,
+        x: i32,
+        y: i32,
+        transform: Transform,
+        mode: PlaceMode,
+    ) -> Result<(), JsValue> {
+        let pattern = pattern.clone().transformed(transform).to_grid(0, 0);
+        let (width, height) = (self.width, self.height);
+        let cells = pattern.placement(x, y, mode, self.shadow_cells()?, width, height);
+        self.set_cells(cells)
+    }
+
+    // (x, y) is the grid cell for the bottom-left corner of the pattern as
+    // shown on screen.
+    fn load_pattern_at(&mut self, pattern: Pattern, x: i32, y: i32) -> Result<(), JsValue> {
+        let pattern = pattern.to_grid(x, y);
+        let positions = pattern.positions_at(0, 0, self.width, self.height);
         let data = self.texture_manager.create_pattern_data(&positions);
         self.upload_texture_data(data)?;
         Ok(())
     }
 
+    fn current_pattern(&self) -> Result<Pattern, JsValue> {
+        let cells = self.simulator.read_cells()?;
+        Ok(Pattern::from_grid(&cells, self.width))
+    }
+
+    fn shadow_cells(&mut self) -> Result<&[u8], JsValue> {
+        if self.shadow.is_none() {
+            self.shadow = Some(self.simulator.read_cells()?);
+        }
+        Ok(self.shadow.as_deref().unwrap_or_default())
+    }
+
+    fn upload_texture_data(&mut self, data: Vec<u8>) -> Result<(), JsValue> {
+        self.record_grid()?;
+        let cells = texture::texture_data_to_cells(&data);
+        self.simulator.load(&cells)?;
+        self.shadow = Some(cells);
+        self.state_version += 1;
+        Ok(())
+    }
+
+    // Bulk changes start a new timeline, since the earlier generations no
+    // longer lead to the new state.
+    fn record_grid(&mut self) -> Result<(), JsValue> {
+        let before = self.capture()?;
+        self.history.record_grid(before);
+        self.timeline.clear();
+        Ok(())
+    }
+
+    fn record_steps(&mut self, generations: u64) -> Result<(), JsValue> {
+        if self.history.needs_step_snapshot() || self.timeline.is_enabled() {
+            let before = self.capture()?;
+            if self.history.needs_step_snapshot() {
+                self.history.record_steps(before.clone());
+            }
+            self.timeline.record(before, generations);
+        }
+        self.timeline.clear_future();
+        Ok(())
+    }
+
+    // Goes back one recorded frame. Returns how many generations that
+    // rewound, as a negative number, or 0 at the start of the timeline.
     #[wasm_bindgen]
-    pub fn load_beacon(&mut self) -> Result<(), JsValue> {
-        let pattern = Pattern::beacon();
-        let positions = pattern.scaled_for_grid(self.width, self.height);
-        let data = self.texture_manager.create_pattern_data(&positions);
-        self.upload_texture_data(data)?;
+    pub fn step_back(&mut self) -> Result<f64, JsValue> {
+        match self.timeline.position() {
+            0 => Ok(0.0),
+            position => self.seek_timeline(position as u32 - 1),
+        }
+    }
+
+    // Shows the recorded frame at `position`, where 0 is the oldest and
+    // `timeline_length()` the newest. Frames of `step_n` cover the whole
+    // batch, so this returns how many generations the grid moved: negative
+    // going back, 0 when nothing changed.
+    #[wasm_bindgen]
+    pub fn seek_timeline(&mut self, position: u32) -> Result<f64, JsValue> {
+        let current = self.capture()?;
+        let before = self.timeline.generation();
+        let Some(frame) = self.timeline.seek(position as usize, current) else {
+            return Ok(0.0);
+        };
+        self.restore(&frame)?;
+        Ok(self.timeline.generation() as f64 - before as f64)
+    }
+
+    // The whole state for undo and the timeline. On the HashLife backend that
+    // is the universe, cells outside the grid and generation count included.
+    fn capture(&mut self) -> Result<Snapshot, JsValue> {
+        if let Some(universe) = self.simulator.universe() {
+            return Ok(Snapshot::from_universe(universe, self.width, self.height));
+        }
+        Ok(Snapshot::from_cells(self.shadow_cells()?))
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) -> Result<(), JsValue> {
+        let Some(universe) = snapshot.to_universe(self.rule) else {
+            return self.load_cells(snapshot.to_cells());
+        };
+        self.simulator.set_universe(universe)?;
+        self.shadow = None;
+        self.state_version += 1;
         Ok(())
     }
 
     #[wasm_bindgen]
-    pub fn load_toad(&mut self) -> Result<(), JsValue> {
-        let pattern = Pattern::toad();
-        let positions = pattern.scaled_for_grid(self.width, self.height);
-        let data = self.texture_manager.create_pattern_data(&positions);
-        self.upload_texture_data(data)?;
+    pub fn timeline_position(&self) -> u32 {
+        self.timeline.position() as u32
+    }
+
+    #[wasm_bindgen]
+    pub fn timeline_length(&self) -> u32 {
+        self.timeline.len() as u32
+    }
+
+    #[wasm_bindgen]
+    pub fn timeline_capacity(&self) -> u32 {
+        self.timeline.capacity() as u32
+    }
+
+    // Number of generations kept for `step_back`. Recording is off (0) by
+    // default, since it reads the grid back on every step.
+    #[wasm_bindgen]
+    pub fn set_timeline_capacity(&mut self, capacity: u32) {
+        self.timeline.set_capacity(capacity as usize);
+    }
+
+    fn load_cells(&mut self, cells: Vec<u8>) -> Result<(), JsValue> {
+        self.simulator.load(&cells)?;
+        self.shadow = Some(cells);
+        self.state_version += 1;
         Ok(())
     }
 
     #[wasm_bindgen]
-    pub fn load_spaceship(&mut self) -> Result<(), JsValue> {
-        let pattern = Pattern::lightweight_spaceship();
-        let positions = pattern.scaled_for_grid(self.width, self.height);
-        let data = self.texture_manager.create_pattern_data(&positions);
-        self.upload_texture_data(data)?;
+    pub fn step(&mut self) -> Result<(), JsValue> {
+        self.record_steps(1)?;
+        self.simulator.step()?;
+        self.shadow = None;
+        self.state_version += 1;
+        self.observe_generation()
+    }
+
+    // Runs `generations` generations in one call. The timeline records the
+    // whole batch as a single frame. With period detection on, every
+    // generation is read back so none is missed. Zero generations changes
+    // nothing and records nothing.
+    #[wasm_bindgen]
+    pub fn step_n(&mut self, generations: u32) -> Result<(), JsValue> {
+        if generations == 0 {
+            return Ok(());
+        }
+        self.record_steps(generations as u64)?;
+        if self.periods.is_some() {
+            for _ in 0..generations {
+                self.simulator.step()?;
+                self.shadow = None;
+                self.state_version += 1;
+                self.observe_generation()?;
+            }
+            return Ok(());
+        }
+
+        self.simulator.step_n(generations)?;
+        self.shadow = None;
+        self.state_version += 1;
         Ok(())
     }
 
+    // Advances 2^k generations; a single call on the HashLife backend can
+    // cover billions of generations. Period detection starts over from the
+    // new generation.
     #[wasm_bindgen]
-    pub fn load_pulsar(&mut self) -> Result<(), JsValue> {
-        let pattern = Pattern::pulsar();
-        let positions = pattern.scaled_for_grid(self.width, self.height);
-        let data = self.texture_manager.create_pattern_data(&positions);
-        self.upload_texture_data(data)?;
+    pub fn step_pow2(&mut self, k: u32) -> Result<(), JsValue> {
+        if k > self.simulator.max_step_pow2() {
+            return Err(JsValue::from_str(&format!(
+                "Cannot step 2^{} generations at once on this backend; the limit is 2^{}",
+                k,
+                self.simulator.max_step_pow2()
+            )));
+        }
+        self.record_steps(1 << k)?;
+        self.simulator.step_pow2(k)?;
+        self.shadow = None;
+        self.state_version += 1;
+        self.restart_period_detection()
+    }
+
+    // Hashes every generation from now on to find when the grid starts
+    // repeating. Off by default, since it reads each generation back.
+    #[wasm_bindgen]
+    pub fn set_period_detection(&mut self, enabled: bool) -> Result<(), JsValue> {
+        match (enabled, self.periods.is_some()) {
+            (true, false) => {
+                self.periods = Some(PeriodDetector::new());
+                self.restart_period_detection()
+            }
+            (false, _) => {
+                self.periods = None;
+                Ok(())
+            }
+            (true, true) => Ok(()),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn period_detection(&self) -> bool {
+        self.periods.is_some()
+    }
+
+    // The cycle the grid settled into, once one has been seen.
+    #[wasm_bindgen]
+    pub fn periodicity(&self) -> Option<Periodicity> {
+        self.periods.as_ref()?.result().cloned()
+    }
+
+    // Generations watched since detection last started over.
+    #[wasm_bindgen]
+    pub fn period_generations(&self) -> u32 {
+        self.periods
+            .as_ref()
+            .map_or(0, |periods| periods.generations())
+    }
+
+    fn restart_period_detection(&mut self) -> Result<(), JsValue> {
+        if let Some(periods) = self.periods.as_mut() {
+            periods.reset();
+            self.periods_version = self.state_version.wrapping_sub(1);
+        }
+        self.observe_generation()
+    }
+
+    // Feeds the current generation to the detector. Edits, undo, timeline
+    // seeks and loads all bump the state version without stepping, which
+    // shows up here as a gap and restarts the detection.
+    fn observe_generation(&mut self) -> Result<(), JsValue> {
+        let Some(mut periods) = self.periods.take() else {
+            return Ok(());
+        };
+        if self.periods_version.wrapping_add(1) != self.state_version {
+            periods.reset();
+        }
+
+        let (width, height) = (self.width, self.height);
+        let result = self.shadow_cells().map(|cells| {
+            periods.observe(cells, width, height);
+        });
+        self.periods = Some(periods);
+        self.periods_version = self.state_version;
+        result
+    }
+
+    // Draws every attached view. The primary view reads the simulator's
+    // texture when it has one; all other views get the cells uploaded once
+    // per change.
+    #[wasm_bindgen]
+    pub fn render(&mut self) -> Result<(), JsValue> {
+        let version = self.state_version;
+        let uses_texture = self.simulator.texture().is_some();
+        let needs_cells = (!uses_texture && self.primary.needs_upload(version))
+            || self
+                .views
+                .iter()
+                .flatten()
+                .any(|view| view.needs_upload(version));
+
+        if needs_cells && self.simulator.host_cells().is_none() {
+            self.shadow_cells()?;
+        }
+        let cells = match self.simulator.host_cells() {
+            Some(cells) => Some(cells),
+            None => self.shadow.as_deref(),
+        };
+
+        if let Some(cells) = cells {
+            if !uses_texture && self.primary.needs_upload(version) {
+                self.primary.upload(cells, version)?;
+            }
+            for view in self.views.iter_mut().flatten() {
+                if view.needs_upload(version) {
+                    view.upload(cells, version)?;
+                }
+            }
+        }
+
+        self.primary
+            .render(self.simulator.texture(), &self.appearance)?;
+        for view in self.views.iter().flatten() {
+            view.render(None, &self.appearance)?;
+        }
+
         Ok(())
     }
 
+    // Palette colours as CSS hex strings. Takes effect on the next render;
+    // no shader is rebuilt.
     #[wasm_bindgen]
-    pub fn load_glider_gun(&mut self) -> Result<(), JsValue> {
-        let pattern = Pattern::gospel_glider_gun();
-        let positions = pattern.scaled_for_grid(self.width, self.height);
-        let data = self.texture_manager.create_pattern_data(&positions);
-        self.upload_texture_data(data)?;
+    pub fn set_colors(
+        &mut self,
+        alive: &str,
+        dead: &str,
+        grid: &str,
+        background: &str,
+    ) -> Result<(), JsValue> {
+        let parse = |color: &str| Color::parse(color).map_err(|e| JsValue::from_str(&e));
+        let palette = &mut self.appearance.palette;
+        palette.states[0] = parse(dead)?;
+        palette.states[1] = parse(alive)?;
+        palette.grid = parse(grid)?;
+        palette.background = parse(background)?;
         Ok(())
     }
 
-    fn upload_texture_data(&mut self, data: Vec<u8>) -> Result<(), JsValue> {
-        // Recreate textures with new data
-        self.current_state = webgl::create_texture(&self.gl, self.width, self.height)?;
-        self.texture_manager
-            .upload_data(&self.current_state, &data)?;
+    // The palette as [alive, dead, grid, background] hex strings.
+    #[wasm_bindgen]
+    pub fn colors(&self) -> Vec<String> {
+        let palette = &self.appearance.palette;
+        [
+            palette.alive(),
+            palette.dead(),
+            palette.grid,
+            palette.background,
+        ]
+        .iter()
+        .map(Color::to_hex)
+        .collect()
+    }
 
-        self.next_state = webgl::create_texture(&self.gl, self.width, self.height)?;
-        self.texture_manager.upload_data(&self.next_state, &data)?;
+    #[wasm_bindgen]
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.appearance.palette = theme.palette();
+    }
 
+    // One colour per cell state, starting with dead and alive. Life-like
+    // rules only have those two; the rest only affect rendering, waiting for
+    // multi-state rules (see color::MAX_STATES), and states past the end use
+    // the last colour.
+    #[wasm_bindgen]
+    pub fn set_state_colors(&mut self, colors: Vec<String>) -> Result<(), JsValue> {
+        let states = colors
+            .iter()
+            .map(|color| Color::parse(color))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| JsValue::from_str(&e))?;
+        let palette = &self.appearance.palette;
+        self.appearance.palette = Palette::new(states, palette.grid, palette.background)
+            .map_err(|e| JsValue::from_str(&e))?;
         Ok(())
     }
 
     #[wasm_bindgen]
-    pub fn step(&mut self) -> Result<(), JsValue> {
-        self.gl
-            .bind_framebuffer(WebGlRenderingContext::FRAMEBUFFER, Some(&self.framebuffer));
-        self.gl.framebuffer_texture_2d(
-            WebGlRenderingContext::FRAMEBUFFER,
-            WebGlRenderingContext::COLOR_ATTACHMENT0,
-            WebGlRenderingContext::TEXTURE_2D,
-            Some(&self.next_state),
-            0,
-        );
-
-        self.gl
-            .viewport(0, 0, self.width as i32, self.height as i32);
-        self.gl.use_program(Some(&self.compute_program));
-
-        self.gl.active_texture(WebGlRenderingContext::TEXTURE0);
-        self.gl
-            .bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(&self.current_state));
-
-        let texture_location = self
-            .gl
-            .get_uniform_location(&self.compute_program, "u_texture");
-        self.gl.uniform1i(texture_location.as_ref(), 0);
-
-        let resolution_location = self
-            .gl
-            .get_uniform_location(&self.compute_program, "u_resolution");
-        self.gl.uniform2f(
-            resolution_location.as_ref(),
-            self.width as f32,
-            self.height as f32,
-        );
+    pub fn set_grid_lines(&mut self, visible: bool) {
+        self.appearance.grid_lines = visible;
+    }
 
-        self.gl
-            .bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&self.quad_buffer));
-        let position_location = self
-            .gl
-            .get_attrib_location(&self.compute_program, "a_position");
-        self.gl.enable_vertex_attrib_array(position_location as u32);
-        self.gl.vertex_attrib_pointer_with_i32(
-            position_location as u32,
-            2,
-            WebGlRenderingContext::FLOAT,
-            false,
-            0,
-            0,
-        );
+    // Ages are tracked by the WebGL backend's compute pass. The CPU and
+    // HashLife backends, and views other than the primary one, draw every
+    // live cell as just born.
+    #[wasm_bindgen]
+    pub fn set_color_mode(&mut self, mode: ColorMode) {
+        self.appearance.mode = mode;
+    }
 
-        self.gl.draw_arrays(WebGlRenderingContext::TRIANGLES, 0, 6);
+    #[wasm_bindgen]
+    pub fn color_mode(&self) -> ColorMode {
+        self.appearance.mode
+    }
 
-        std::mem::swap(&mut self.current_state, &mut self.next_state);
+    // Colours for age mode as CSS hex strings, from just born to `max_age`
+    // generations old (at most 255).
+    #[wasm_bindgen]
+    pub fn set_age_gradient(&mut self, colors: Vec<String>, max_age: u32) -> Result<(), JsValue> {
+        let stops = colors
+            .iter()
+            .map(|color| Color::parse(color))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| JsValue::from_str(&e))?;
+        self.appearance.gradient =
+            Gradient::new(stops, max_age).map_err(|e| JsValue::from_str(&e))?;
+        Ok(())
+    }
 
+    // Ghost trails: cells that died within the last `generations` generations
+    // fade from `color` (a CSS hex string) to dead. 0 turns trails off. Like
+    // ages, trails come from the WebGL backend's compute pass.
+    #[wasm_bindgen]
+    pub fn set_trail(&mut self, generations: u32, color: &str) -> Result<(), JsValue> {
+        if generations > color::MAX_TRAIL_LENGTH {
+            return Err(JsValue::from_str(&format!(
+                "Trails can be at most {} generations long",
+                color::MAX_TRAIL_LENGTH
+            )));
+        }
+        self.appearance.trail_color = Color::parse(color).map_err(|e| JsValue::from_str(&e))?;
+        self.appearance.trail_length = generations;
         Ok(())
     }
 
     #[wasm_bindgen]
-    pub fn render(&self) -> Result<(), JsValue> {
-        self.gl
-            .bind_framebuffer(WebGlRenderingContext::FRAMEBUFFER, None);
+    pub fn trail_length(&self) -> u32 {
+        self.appearance.trail_length
+    }
 
-        let canvas = web_sys::window()
-            .unwrap()
-            .document()
-            .unwrap()
-            .get_element_by_id("gameCanvas")
-            .unwrap();
-        let canvas: web_sys::HtmlCanvasElement =
-            canvas.dyn_into::<web_sys::HtmlCanvasElement>().unwrap();
+    // Attaches another canvas showing the same simulation and returns its view
+    // id. The primary view has id 0.
+    #[wasm_bindgen]
+    pub fn attach_view(&mut self, canvas_id: &str) -> Result<u32, JsValue> {
+        self.attach_target(RenderTarget::from_id(canvas_id)?)
+    }
 
-        self.gl
-            .viewport(0, 0, canvas.width() as i32, canvas.height() as i32);
-        self.gl.use_program(Some(&self.render_program));
+    #[wasm_bindgen]
+    pub fn attach_canvas(&mut self, canvas: HtmlCanvasElement) -> Result<u32, JsValue> {
+        self.attach_target(RenderTarget::Canvas(canvas))
+    }
 
-        self.gl.clear_color(0.1, 0.1, 0.1, 1.0);
-        self.gl.clear(WebGlRenderingContext::COLOR_BUFFER_BIT);
+    #[wasm_bindgen]
+    pub fn attach_offscreen_canvas(&mut self, canvas: OffscreenCanvas) -> Result<u32, JsValue> {
+        self.attach_target(RenderTarget::Offscreen(canvas))
+    }
 
-        self.gl.active_texture(WebGlRenderingContext::TEXTURE0);
-        self.gl
-            .bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(&self.current_state));
+    fn attach_target(&mut self, target: RenderTarget) -> Result<u32, JsValue> {
+        let gl = webgl::init_webgl_context(&target)?;
+        let view = View::new(target, gl, self.width, self.height)?;
+        self.views.push(Some(view));
+        Ok(self.views.len() as u32)
+    }
 
-        let texture_location = self
-            .gl
-            .get_uniform_location(&self.render_program, "u_texture");
-        if texture_location.is_none() {
-            return Err(JsValue::from_str("u_texture uniform not found"));
+    #[wasm_bindgen]
+    pub fn detach_view(&mut self, id: u32) -> Result<(), JsValue> {
+        if id == 0 {
+            return Err(JsValue::from_str("The primary view cannot be detached"));
         }
+        self.view(id)?;
+        self.views[id as usize - 1] = None;
+        Ok(())
+    }
 
-        self.gl.uniform1i(texture_location.as_ref(), 0);
+    #[wasm_bindgen]
+    pub fn pan_view(&mut self, id: u32, dx: f64, dy: f64) -> Result<(), JsValue> {
+        let view = self.view_mut(id)?;
+        let (canvas_width, canvas_height) = view.canvas_size();
+        view.camera.pan(dx, dy, canvas_width, canvas_height);
+        Ok(())
+    }
 
-        self.gl
-            .bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&self.quad_buffer));
-        let position_location = self
-            .gl
-            .get_attrib_location(&self.render_program, "a_position");
-        if position_location == -1 {
-            return Err(JsValue::from_str("a_position attribute not found"));
-        }
+    #[wasm_bindgen]
+    pub fn zoom_view_at(&mut self, id: u32, x: f64, y: f64, factor: f64) -> Result<(), JsValue> {
+        let view = self.view_mut(id)?;
+        let (canvas_width, canvas_height) = view.canvas_size();
+        view.camera
+            .zoom_at(x, y, factor, canvas_width, canvas_height);
+        Ok(())
+    }
 
-        self.gl.enable_vertex_attrib_array(position_location as u32);
-        self.gl.vertex_attrib_pointer_with_i32(
-            position_location as u32,
-            2,
-            WebGlRenderingContext::FLOAT,
-            false,
-            0,
-            0,
-        );
+    #[wasm_bindgen]
+    pub fn reset_view_camera(&mut self, id: u32) -> Result<(), JsValue> {
+        self.view_mut(id)?.camera = Camera::new();
+        Ok(())
+    }
 
-        self.gl.draw_arrays(WebGlRenderingContext::TRIANGLES, 0, 6);
+    #[wasm_bindgen]
+    pub fn view_screen_to_cell(
+        &self,
+        id: u32,
+        px: f64,
+        py: f64,
+    ) -> Result<Option<Vec<u32>>, JsValue> {
+        let view = self.view(id)?;
+        let (canvas_width, canvas_height) = view.canvas_size();
+        Ok(view
+            .camera
+            .screen_to_cell(px, py, canvas_width, canvas_height, self.width, self.height)
+            .map(|(x, y)| vec![x, y]))
+    }
 
-        let error = self.gl.get_error();
-        if error != WebGlRenderingContext::NO_ERROR {
-            web_sys::console::log_1(&format!("WebGL error: {}", error).into());
+    fn view(&self, id: u32) -> Result<&View, JsValue> {
+        if id == 0 {
+            return Ok(&self.primary);
         }
+        self.views
+            .get(id as usize - 1)
+            .and_then(Option::as_ref)
+            .ok_or_else(|| JsValue::from_str(&format!("No view with id {}", id)))
+    }
 
-        Ok(())
+    fn view_mut(&mut self, id: u32) -> Result<&mut View, JsValue> {
+        if id == 0 {
+            return Ok(&mut self.primary);
+        }
+        self.views
+            .get_mut(id as usize - 1)
+            .and_then(Option::as_mut)
+            .ok_or_else(|| JsValue::from_str(&format!("No view with id {}", id)))
+    }
+
+    // Pans the view by a drag of (dx, dy) canvas pixels.
+    #[wasm_bindgen]
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        let (canvas_width, canvas_height) = self.primary.canvas_size();
+        self.primary.camera.pan(dx, dy, canvas_width, canvas_height);
+    }
+
+    // Zooms by `factor` keeping the point under canvas pixel (x, y) fixed.
+    #[wasm_bindgen]
+    pub fn zoom_at(&mut self, x: f64, y: f64, factor: f64) {
+        let (canvas_width, canvas_height) = self.primary.canvas_size();
+        self.primary
+            .camera
+            .zoom_at(x, y, factor, canvas_width, canvas_height);
+    }
+
+    #[wasm_bindgen]
+    pub fn zoom(&self) -> f64 {
+        self.primary.camera.zoom
+    }
+
+    #[wasm_bindgen]
+    pub fn reset_view(&mut self) {
+        self.primary.camera = Camera::new();
+    }
+
+    // Returns [x, y] of the cell under canvas pixel (px, py), or undefined
+    // when the pixel is outside the grid.
+    #[wasm_bindgen]
+    pub fn screen_to_cell(&self, px: f64, py: f64) -> Option<Vec<u32>> {
+        let (canvas_width, canvas_height) = self.primary.canvas_size();
+        self.primary
+            .camera
+            .screen_to_cell(px, py, canvas_width, canvas_height, self.width, self.height)
+            .map(|(x, y)| vec![x, y])
+    }
+
+    // Returns the canvas pixel [px, py] at the centre of a cell.
+    #[wasm_bindgen]
+    pub fn cell_to_screen(&self, x: u32, y: u32) -> Vec<f64> {
+        let (canvas_width, canvas_height) = self.primary.canvas_size();
+        let (px, py) = self.primary.camera.cell_to_screen(
+            x,
+            y,
+            canvas_width,
+            canvas_height,
+            self.width,
+            self.height,
+        );
+        vec![px, py]
     }
 
     #[wasm_bindgen]
@@ -273,12 +1020,20 @@ impl GameOfLife {
         self.width = width;
         self.height = height;
 
-        self.current_state = webgl::create_texture(&self.gl, width, height)?;
-        self.next_state = webgl::create_texture(&self.gl, width, height)?;
+        self.primary.resize(width, height)?;
+        for view in self.views.iter_mut().flatten() {
+            view.resize(width, height)?;
+        }
+        self.simulator = create_simulator(self.primary.gl(), self.backend, width, height)?;
+        self.shadow = None;
+        self.simulator.set_rule(self.rule);
+        self.simulator.set_topology(self.topology);
 
-        self.texture_manager = TextureManager::new(self.gl.clone(), width, height);
+        self.texture_manager = TextureManager::new(self.primary.gl().clone(), width, height);
 
         self.clear()?;
+        self.history.clear();
+        self.timeline.clear();
 
         Ok(())
     }
@@ -289,7 +1044,9 @@ impl GameOfLife {
             return Ok(());
         }
 
-        self.set_cell(x, y, 255)?;
+        let index = (y * self.width + x) as usize;
+        let alive = self.shadow_cells()?[index] != 0;
+        self.set_cell(x, y, if alive { 0 } else { 255 })?;
         Ok(())
     }
 
@@ -299,29 +1056,134 @@ impl GameOfLife {
             return Ok(());
         }
 
-        self.gl
-            .bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(&self.current_state));
-
-        let data = [alive, alive, alive, 255];
+        let index = y * self.width + x;
+        let before = self.shadow_cells()?[index as usize];
+        let after = (alive != 0) as u8;
+        if before != after {
+            self.history.record_cell(index, before);
+            self.timeline.clear_future();
+            self.write_cell(index, after)?;
+        }
 
-        self.gl
-            .tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_u8_array(
-                WebGlRenderingContext::TEXTURE_2D,
-                0,
-                x as i32,
-                y as i32,
-                1,
-                1,
-                WebGlRenderingContext::RGBA,
-                WebGlRenderingContext::UNSIGNED_BYTE,
-                Some(&data),
-            )?;
+        Ok(())
+    }
 
+    // Sets a cell without recording it in the history.
+    fn write_cell(&mut self, index: u32, alive: u8) -> Result<(), JsValue> {
+        self.simulator
+            .set_cell(index % self.width, index / self.width, alive != 0)?;
+        if let Some(shadow) = self.shadow.as_mut() {
+            shadow[index as usize] = alive;
+        }
+        self.state_version += 1;
         Ok(())
     }
 
+    // Applies the cells as one undoable edit.
+    fn set_cells(&mut self, cells: Vec<(u32, u32, u8)>) -> Result<(), JsValue> {
+        self.history.begin_group();
+        let result = cells
+            .into_iter()
+            .try_for_each(|(x, y, alive)| self.set_cell(x, y, alive));
+        self.history.end_group();
+        result
+    }
+
+    // Undoes the last edit, bulk change or run of steps. Returns false when
+    // there is nothing to undo.
     #[wasm_bindgen]
-    pub fn draw_line(&mut self, x1: u32, y1: u32, x2: u32, y2: u32) -> Result<(), JsValue> {
+    pub fn undo(&mut self) -> Result<bool, JsValue> {
+        let Some(entry) = self.history.pop_undo() else {
+            return Ok(false);
+        };
+        let entry = self.apply_history_entry(entry)?;
+        self.history.push_redo(entry);
+        self.timeline.clear();
+        Ok(true)
+    }
+
+    #[wasm_bindgen]
+    pub fn redo(&mut self) -> Result<bool, JsValue> {
+        let Some(entry) = self.history.pop_redo() else {
+            return Ok(false);
+        };
+        let entry = self.apply_history_entry(entry)?;
+        self.history.push_undo(entry);
+        self.timeline.clear();
+        Ok(true)
+    }
+
+    #[wasm_bindgen]
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    #[wasm_bindgen]
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    // Edits made between these calls are undone together, e.g. a brush
+    // stroke spanning several mouse events.
+    #[wasm_bindgen]
+    pub fn begin_edit_group(&mut self) {
+        self.history.begin_group();
+    }
+
+    #[wasm_bindgen]
+    pub fn end_edit_group(&mut self) {
+        self.history.end_group();
+    }
+
+    // Oldest entries are forgotten once the history holds more than `bytes`.
+    #[wasm_bindgen]
+    pub fn set_history_budget(&mut self, bytes: u32) {
+        self.history.set_budget(bytes as usize);
+    }
+
+    #[wasm_bindgen]
+    pub fn history_budget(&self) -> u32 {
+        self.history.budget().min(u32::MAX as usize) as u32
+    }
+
+    #[wasm_bindgen]
+    pub fn history_size(&self) -> u32 {
+        self.history.size().min(u32::MAX as usize) as u32
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    // Swaps the entry's contents into the grid and returns what it replaced.
+    fn apply_history_entry(&mut self, entry: Entry) -> Result<Entry, JsValue> {
+        match entry {
+            Entry::Cells(cells) => {
+                let mut replaced = Vec::with_capacity(cells.len());
+                for (index, value) in cells {
+                    replaced.push((index, self.shadow_cells()?[index as usize]));
+                    self.write_cell(index, value)?;
+                }
+                Ok(Entry::Cells(replaced))
+            }
+            Entry::Grid(snapshot) => {
+                let replaced = self.capture()?;
+                self.restore(&snapshot)?;
+                Ok(Entry::Grid(replaced))
+            }
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn draw_line(
+        &mut self,
+        x1: u32,
+        y1: u32,
+        x2: u32,
+        y2: u32,
+        alive: u8,
+    ) -> Result<(), JsValue> {
         // Bresenham's line algorithm
         let mut x0 = x1 as i32;
         let mut y0 = y1 as i32;
@@ -333,10 +1195,11 @@ impl GameOfLife {
         let sx = if x0 < x_end { 1 } else { -1 };
         let sy = if y0 < y_end { 1 } else { -1 };
         let mut err = dx + dy;
+        let mut cells = Vec::new();
 
         loop {
             if x0 >= 0 && y0 >= 0 && (x0 as u32) < self.width && (y0 as u32) < self.height {
-                self.set_cell(x0 as u32, y0 as u32, 255)?;
+                cells.push((x0 as u32, y0 as u32, alive));
             }
 
             if x0 == x_end && y0 == y_end {
@@ -354,7 +1217,7 @@ impl GameOfLife {
             }
         }
 
-        Ok(())
+        self.set_cells(cells)
     }
 
     #[wasm_bindgen]
@@ -363,9 +1226,38 @@ impl GameOfLife {
         center_x: u32,
         center_y: u32,
         radius: u32,
+        alive: u8,
     ) -> Result<(), JsValue> {
-        self.texture_manager
-            .add_cells_in_area(&self.current_state, center_x, center_y, radius)?;
-        Ok(())
+        let mut cells = Vec::new();
+        for dy in -(radius as i32)..=(radius as i32) {
+            for dx in -(radius as i32)..=(radius as i32) {
+                let x = center_x as i32 + dx;
+                let y = center_y as i32 + dy;
+
+                if x >= 0
+                    && y >= 0
+                    && (x as u32) < self.width
+                    && (y as u32) < self.height
+                    && dx * dx + dy * dy <= (radius as i32) * (radius as i32)
+                {
+                    cells.push((x as u32, y as u32, alive));
+                }
+            }
+        }
+
+        self.set_cells(cells)
     }
 }
+
+fn create_simulator(
+    gl: &WebGlRenderingContext,
+    backend: Backend,
+    width: u32,
+    height: u32,
+) -> Result<Box<dyn Simulator>, JsValue> {
+    Ok(match backend {
+        Backend::WebGl => Box::new(GpuSimulator::new(gl, width, height)?),
+        Backend::Cpu => Box::new(CpuSimulator::new(width, height)),
+        Backend::HashLife => Box::new(HashLifeSimulator::new(width, height)),
+    })
+}
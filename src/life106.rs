@@ -0,0 +1,60 @@
+use crate::patterns::Pattern;
+
+const HEADER: &str = "#Life 1.06";
+
+pub fn parse(text: &str) -> Result<Pattern, String> {
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+
+    if lines.next() != Some(HEADER) {
+        return Err(format!("Missing \"{}\" header", HEADER));
+    }
+
+    let mut pattern = Pattern::new("", Vec::new());
+
+    for line in lines {
+        if line.starts_with('#') {
+            continue;
+        }
+
+        let mut coordinates = line.split_whitespace().map(str::parse::<i32>);
+        match (coordinates.next(), coordinates.next(), coordinates.next()) {
+            (Some(Ok(x)), Some(Ok(y)), None) => pattern.cells.push((x, y)),
+            _ => return Err(format!("Invalid Life 1.06 coordinate line \"{}\"", line)),
+        }
+    }
+
+    Ok(pattern)
+}
+
+pub fn write(pattern: &Pattern) -> String {
+    let mut output = format!("{}\n", HEADER);
+
+    for &(x, y) in &pattern.cells {
+        output.push_str(&format!("{} {}\n", x, y));
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coordinates_may_be_negative() {
+        let text = "#Life 1.06\n#D A glider\n0 -1\n1 0\n-1 1\n0 1\n1 1\n";
+        let pattern = parse(text).unwrap();
+        assert_eq!(pattern.cells, [(0, -1), (1, 0), (-1, 1), (0, 1), (1, 1)]);
+        assert_eq!(parse(&write(&pattern)).unwrap().cells, pattern.cells);
+    }
+
+    #[test]
+    fn header_and_coordinate_pairs_are_required() {
+        assert!(parse("0 0\n").is_err());
+        assert!(parse("#Life 1.05\n0 0\n").is_err());
+        assert!(parse("#Life 1.06\n0\n").is_err());
+        assert!(parse("#Life 1.06\n0 0 0\n").is_err());
+        assert!(parse("#Life 1.06\n0 x\n").is_err());
+        assert!(parse("#Life 1.06\n").unwrap().cells.is_empty());
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+
+use crate::hashlife::{ALIVE, DEAD, MAX_LEVEL, NodeId, Universe};
+use crate::patterns::Pattern;
+
+const LEAF_LEVEL: u32 = 3;
+const LEAF_SIZE: usize = 1 << LEAF_LEVEL;
+
+// Reads Golly's two-state Macrocell format. The universe keeps the file's
+// coordinates (the root is centred on the origin) and its #G generation; the
+// #R rule is returned as written for the caller to apply.
+pub fn parse(text: &str) -> Result<(Universe, Option<String>), String> {
+    let mut lines = text.lines();
+    let header = lines.next().unwrap_or("").trim();
+    if !header.starts_with("[M2]") {
+        return Err("Missing \"[M2]\" Macrocell header".to_string());
+    }
+
+    let mut universe = Universe::new();
+    let mut rule = None;
+    // Node 0 is the empty node of whatever size its parent needs.
+    let mut nodes: Vec<NodeId> = vec![DEAD];
+
+    for line in lines {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut chars = comment.chars();
+            let tag = chars.next();
+            let value = chars.as_str().trim();
+            match tag {
+                Some('R') => rule = Some(value.to_string()),
+                Some('G') => universe.set_generation(
+                    value
+                        .parse()
+                        .map_err(|_| format!("Invalid generation \"{}\"", value))?,
+                ),
+                _ => {}
+            }
+            continue;
+        }
+
+        let node = if line.starts_with(['.', '*', '$']) {
+            parse_leaf(&mut universe, line)?
+        } else {
+            parse_node(&mut universe, &nodes, line)?
+        };
+        nodes.push(node);
+    }
+
+    match nodes.last() {
+        Some(&root) if nodes.len() > 1 => universe.set_root(root),
+        _ => return Err("Macrocell file contains no nodes".to_string()),
+    }
+
+    Ok((universe, rule))
+}
+
+fn parse_leaf(universe: &mut Universe, line: &str) -> Result<NodeId, String> {
+    let mut grid = [[false; LEAF_SIZE]; LEAF_SIZE];
+    let (mut x, mut y) = (0, 0);
+
+    for c in line.chars() {
+        match c {
+            '$' => {
+                x = 0;
+                y += 1;
+            }
+            '.' | '*' => {
+                if x >= LEAF_SIZE || y >= LEAF_SIZE {
+                    return Err(format!("Leaf node \"{}\" is larger than 8x8", line));
+                }
+                grid[y][x] = c == '*';
+                x += 1;
+            }
+            _ => return Err(format!("Unexpected character '{}' in leaf node", c)),
+        }
+    }
+
+    Ok(build_leaf(universe, &grid, 0, 0, LEAF_LEVEL))
+}
+
+fn build_leaf(
+    universe: &mut Universe,
+    grid: &[[bool; LEAF_SIZE]; LEAF_SIZE],
+    x: usize,
+    y: usize,
+    level: u32,
+) -> NodeId {
+    if level == 0 {
+        return if grid[y][x] { ALIVE } else { DEAD };
+    }
+
+    let half = 1 << (level - 1);
+    let nw = build_leaf(universe, grid, x, y, level - 1);
+    let ne = build_leaf(universe, grid, x + half, y, level - 1);
+    let sw = build_leaf(universe, grid, x, y + half, level - 1);
+    let se = build_leaf(universe, grid, x + half, y + half, level - 1);
+    universe.join(nw, ne, sw, se)
+}
+
+fn parse_node(universe: &mut Universe, nodes: &[NodeId], line: &str) -> Result<NodeId, String> {
+    let fields = line
+        .split_whitespace()
+        .map(|field| field.parse::<usize>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("Invalid node \"{}\"", line))?;
+
+    let [level, children @ ..] = fields.as_slice() else {
+        return Err(format!("Invalid node \"{}\"", line));
+    };
+    if children.len() != 4 || *level <= LEAF_LEVEL as usize || *level > MAX_LEVEL as usize {
+        return Err(format!("Invalid node \"{}\"", line));
+    }
+
+    let level = *level as u32;
+    let mut ids = [DEAD; 4];
+    for (id, &child) in ids.iter_mut().zip(children) {
+        *id = match child {
+            0 => universe.empty(level - 1),
+            child => *nodes
+                .get(child)
+                .ok_or_else(|| format!("Node \"{}\" refers to a later node", line))?,
+        };
+        if universe.node(*id).level != level - 1 {
+            return Err(format!("Node \"{}\" has children of the wrong size", line));
+        }
+    }
+
+    Ok(universe.join(ids[0], ids[1], ids[2], ids[3]))
+}
+
+pub fn write(universe: &Universe, rule: &str) -> String {
+    let mut output = String::from("[M2] (game-of-life)\n");
+    output.push_str(&format!("#R {}\n", rule));
+    if universe.generation() != 0 {
+        output.push_str(&format!("#G {}\n", universe.generation()));
+    }
+
+    let mut numbers = HashMap::new();
+    let mut count = 0;
+    write_node(
+        universe,
+        universe.root(),
+        &mut numbers,
+        &mut count,
+        &mut output,
+    );
+    if count == 0 {
+        // An empty universe still needs a root.
+        output.push_str(&"$".repeat(LEAF_SIZE));
+        output.push('\n');
+    }
+
+    output
+}
+
+// Writes the children before the node itself and returns its line number,
+// or 0 for empty nodes.
+fn write_node(
+    universe: &Universe,
+    id: NodeId,
+    numbers: &mut HashMap<NodeId, usize>,
+    count: &mut usize,
+    output: &mut String,
+) -> usize {
+    if universe.node_population(id) == 0 {
+        return 0;
+    }
+    if let Some(&number) = numbers.get(&id) {
+        return number;
+    }
+
+    let node = universe.node(id);
+    if node.level == LEAF_LEVEL {
+        for y in 0..LEAF_SIZE {
+            let row: String = (0..LEAF_SIZE)
+                .map(|x| {
+                    if leaf_cell(universe, id, x, y) {
+                        '*'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            output.push_str(row.trim_end_matches('.'));
+            output.push('$');
+        }
+        output.push('\n');
+    } else {
+        let children = [node.nw, node.ne, node.sw, node.se]
+            .map(|child| write_node(universe, child, numbers, count, output));
+        output.push_str(&format!(
+            "{} {} {} {} {}\n",
+            node.level, children[0], children[1], children[2], children[3]
+        ));
+    }
+
+    *count += 1;
+    numbers.insert(id, *count);
+    *count
+}
+
+// Parsed files keep Golly's coordinates, so most have live cells at negative
+// x and y and would miss the grid. Moves the cells into grid coordinates the
+// way the other formats are loaded: flipped upright with the bounding box's
+// bottom-left corner at (x, y). The rule and generation are kept.
+pub fn to_grid(universe: &Universe, x: i32, y: i32) -> Result<Universe, String> {
+    let pattern = universe.to_pattern()?.to_grid(x, y);
+    with_cells(universe, &pattern)
+}
+
+// The reverse for export: grid rows flipped back so the file reads top row
+// first.
+pub fn from_grid(universe: &Universe) -> Result<Universe, String> {
+    let pattern = universe.to_pattern()?.flipped_y();
+    with_cells(universe, &pattern)
+}
+
+fn with_cells(universe: &Universe, pattern: &Pattern) -> Result<Universe, String> {
+    let mut placed = Universe::new();
+    placed.set_rule(universe.rule());
+    placed.set_generation(universe.generation());
+    placed.add_pattern(pattern, 0, 0)?;
+    Ok(placed)
+}
+
+fn leaf_cell(universe: &Universe, id: NodeId, x: usize, y: usize) -> bool {
+    let node = universe.node(id);
+    if node.level == 0 {
+        return id == ALIVE;
+    }
+
+    let half = 1 << (node.level - 1);
+    match (x >= half, y >= half) {
+        (false, false) => leaf_cell(universe, node.nw, x, y),
+        (true, false) => leaf_cell(universe, node.ne, x - half, y),
+        (false, true) => leaf_cell(universe, node.sw, x, y - half),
+        (true, true) => leaf_cell(universe, node.se, x - half, y - half),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpu::CpuSimulator;
+    use crate::hashlife::HashLifeSimulator;
+    use crate::simulator::Simulator;
+
+    fn sorted(mut cells: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+        cells.sort_unstable_by_key(|&(x, y)| (y, x));
+        cells
+    }
+
+    #[test]
+    fn rule_and_generation_headers() {
+        // A glider in the south-west leaf of a 16x16 root centred on the
+        // origin.
+        let text = "[M2] (golly 4.2)\n#R B36/S23\n#G 42\n.*$..*$***$\n4 0 0 1 0\n";
+        let (universe, rule) = parse(text).unwrap();
+        assert_eq!(rule.as_deref(), Some("B36/S23"));
+        assert_eq!(universe.generation(), 42);
+        assert_eq!(
+            sorted(universe.cells()),
+            [(-7, 0), (-6, 1), (-8, 2), (-7, 2), (-6, 2)]
+        );
+    }
+
+    #[test]
+    fn written_files_read_back() {
+        let mut universe = Universe::new();
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2), (-40, -30)] {
+            universe.set_cell(x, y, true).unwrap();
+        }
+        universe.set_generation(7);
+        let text = write(&universe, "B3/S23");
+        assert!(text.starts_with("[M2]"));
+        assert!(text.contains("#R B3/S23\n#G 7\n"));
+
+        let (read, rule) = parse(&text).unwrap();
+        assert_eq!(rule.as_deref(), Some("B3/S23"));
+        assert_eq!(read.generation(), 7);
+        assert_eq!(sorted(read.cells()), sorted(universe.cells()));
+    }
+
+    #[test]
+    fn origin_centred_files_land_on_the_grid() {
+        let text = "[M2] (golly 4.2)\n#G 42\n.*$..*$***$\n4 0 0 1 0\n";
+        let (universe, _) = parse(text).unwrap();
+        let placed = to_grid(&universe, 4, 2).unwrap();
+        assert_eq!(placed.generation(), 42);
+
+        // Upright on screen: the file's top row is the highest grid row.
+        let expected = [(5, 4), (6, 3), (4, 2), (5, 2), (6, 2)];
+        assert_eq!(sorted(placed.cells()), sorted(expected.to_vec()));
+
+        let grid_cells = |simulator: &dyn Simulator| {
+            let cells = simulator.read_cells().unwrap();
+            let mut live: Vec<(i64, i64)> = (0..cells.len())
+                .filter(|&index| cells[index] != 0)
+                .map(|index| ((index % 10) as i64, (index / 10) as i64))
+                .collect();
+            live.sort_unstable_by_key(|&(x, y)| (y, x));
+            live
+        };
+        let mut cpu = CpuSimulator::new(10, 8);
+        cpu.set_universe(to_grid(&universe, 4, 2).unwrap()).unwrap();
+        assert_eq!(grid_cells(&cpu), sorted(expected.to_vec()));
+        let mut hashlife = HashLifeSimulator::new(10, 8);
+        hashlife.set_universe(placed).unwrap();
+        assert_eq!(grid_cells(&hashlife), sorted(expected.to_vec()));
+
+        // Exporting flips the rows back.
+        let exported = from_grid(hashlife.universe().unwrap()).unwrap();
+        let (read, _) = parse(&write(&exported, "B3/S23")).unwrap();
+        let shape = |universe: &Universe| {
+            universe
+                .to_pattern()
+                .unwrap()
+                .normalized()
+                .cells
+                .into_iter()
+                .map(|(x, y)| (x as i64, y as i64))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(sorted(shape(&read)), sorted(shape(&universe)));
+        assert_eq!(read.generation(), 42);
+    }
+
+    #[test]
+    fn malformed_files_are_rejected() {
+        assert!(parse(".*$\n").is_err());
+        assert!(parse("[M2]\n").is_err());
+        assert!(parse("[M2]\n#G soon\n.*$\n").is_err());
+        assert!(parse("[M2]\n4 0 0 2 0\n.*$\n").is_err());
+        assert!(parse("[M2]\n.........*$\n").is_err());
+        assert!(parse("[M2]\n.*$\n5 0 0 1 0\n").is_err());
+    }
+}
@@ -1,220 +1,286 @@
+use wasm_bindgen::prelude::*;
+
+use crate::apgcode;
+use crate::census;
+use crate::rle;
+use crate::rule::Rule;
+
+#[wasm_bindgen]
 #[derive(Clone)]
 pub struct Pattern {
+    #[wasm_bindgen(skip)]
     pub cells: Vec<(i32, i32)>,
-    pub name: &'static str,
+    #[wasm_bindgen(skip)]
+    pub name: String,
+    #[wasm_bindgen(skip)]
+    pub comments: Vec<String>,
 }
 
-impl Pattern {
-    pub fn glider() -> Self {
-        Pattern {
-            cells: vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)],
-            name: "Glider",
+// The eight symmetries of the square, acting on pattern coordinates where y
+// grows downwards as in pattern files. Patterns are placed upright (see
+// `Pattern::to_grid`), so the rotations turn clockwise on screen.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Transform {
+    #[default]
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    // Mirrors left and right.
+    FlipX,
+    // Mirrors top and bottom.
+    FlipY,
+    // Swaps x and y.
+    FlipDiagonal,
+    FlipAntiDiagonal,
+}
+
+impl Transform {
+    pub const ALL: [Transform; 8] = [
+        Transform::Identity,
+        Transform::Rotate90,
+        Transform::Rotate180,
+        Transform::Rotate270,
+        Transform::FlipX,
+        Transform::FlipY,
+        Transform::FlipDiagonal,
+        Transform::FlipAntiDiagonal,
+    ];
+
+    pub fn apply(&self, x: i32, y: i32) -> (i32, i32) {
+        match self {
+            Transform::Identity => (x, y),
+            Transform::Rotate90 => (-y, x),
+            Transform::Rotate180 => (-x, -y),
+            Transform::Rotate270 => (y, -x),
+            Transform::FlipX => (-x, y),
+            Transform::FlipY => (x, -y),
+            Transform::FlipDiagonal => (y, x),
+            Transform::FlipAntiDiagonal => (-y, -x),
         }
     }
+}
 
-    pub fn blinker() -> Self {
-        Pattern {
-            cells: vec![(-1, 0), (0, 0), (1, 0)],
-            name: "Blinker",
+// How a placed pattern combines with the cells already on the grid.
+// Overwrite and And act on the pattern's whole bounding box, so its dead
+// cells clear the grid there; Or and Xor only touch its live cells.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlaceMode {
+    #[default]
+    Overwrite,
+    Or,
+    Xor,
+    And,
+}
+
+impl PlaceMode {
+    pub fn combine(&self, current: bool, pattern: bool) -> bool {
+        match self {
+            PlaceMode::Overwrite => pattern,
+            PlaceMode::Or => current || pattern,
+            PlaceMode::Xor => current != pattern,
+            PlaceMode::And => current && pattern,
         }
     }
+}
+
+#[wasm_bindgen]
+impl Pattern {
+    // Reads an RLE pattern for `GameOfLife::place_pattern`; any rule in the
+    // header is ignored.
+    #[wasm_bindgen]
+    pub fn from_rle(text: &str) -> Result<Pattern, JsValue> {
+        let (pattern, _) = rle::parse(text).map_err(|e| JsValue::from_str(&e))?;
+        Ok(pattern)
+    }
 
-    pub fn beacon() -> Self {
-        Pattern {
-            cells: vec![(0, 0), (1, 0), (0, 1), (3, 2), (2, 3), (3, 3)],
-            name: "Beacon",
-        }
+    // Builds a pattern from an xs, xp or xq apgcode such as "xq4_153".
+    #[wasm_bindgen]
+    pub fn from_apgcode(code: &str) -> Result<Pattern, JsValue> {
+        apgcode::decode(code).map_err(|e| JsValue::from_str(&e))
     }
 
-    pub fn toad() -> Self {
-        Pattern {
-            cells: vec![(1, 0), (2, 0), (3, 0), (0, 1), (1, 1), (2, 1)],
-            name: "Toad",
-        }
+    // The canonical apgcode of the pattern under `rule`, the same whichever
+    // way round or in whichever phase it was drawn. Fails for patterns that
+    // are not still lifes, oscillators or spaceships.
+    #[wasm_bindgen]
+    pub fn apgcode(&self, rule: &str) -> Result<String, JsValue> {
+        let rule = Rule::parse(rule).map_err(|e| JsValue::from_str(&e))?;
+        census::identify(self.cells.clone(), rule)
+            .apgcode
+            .ok_or_else(|| JsValue::from_str("The pattern does not repeat, so it has no apgcode"))
+    }
+
+    #[wasm_bindgen(js_name = name)]
+    pub fn name_js(&self) -> String {
+        self.name.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn population(&self) -> u32 {
+        self.cells.len() as u32
+    }
+
+    #[wasm_bindgen]
+    pub fn width(&self) -> u32 {
+        self.bounds()
+            .map_or(0, |(min_x, _, max_x, _)| (max_x - min_x + 1) as u32)
+    }
+
+    #[wasm_bindgen]
+    pub fn height(&self) -> u32 {
+        self.bounds()
+            .map_or(0, |(_, min_y, _, max_y)| (max_y - min_y + 1) as u32)
     }
+}
 
-    pub fn lightweight_spaceship() -> Self {
+impl Pattern {
+    pub fn new(name: &str, cells: Vec<(i32, i32)>) -> Self {
         Pattern {
-            cells: vec![
-                (0, 1),
-                (3, 1),
-                (4, 2),
-                (0, 3),
-                (4, 3),
-                (1, 4),
-                (2, 4),
-                (3, 4),
-                (4, 4),
-            ],
-            name: "Lightweight Spaceship",
+            cells,
+            name: name.to_string(),
+            comments: Vec::new(),
         }
     }
 
-    pub fn pulsar() -> Self {
-        let mut cells = Vec::new();
+    pub fn bounds(&self) -> Option<(i32, i32, i32, i32)> {
+        let (first_x, first_y) = *self.cells.first()?;
+        Some(self.cells.iter().fold(
+            (first_x, first_y, first_x, first_y),
+            |(min_x, min_y, max_x, max_y), &(x, y)| {
+                (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+            },
+        ))
+    }
 
-        for x in 2..=4 {
-            cells.push((x, 0));
-        }
-        for x in 8..=10 {
-            cells.push((x, 0));
+    pub fn transformed(mut self, transform: Transform) -> Self {
+        for cell in &mut self.cells {
+            *cell = transform.apply(cell.0, cell.1);
         }
+        self
+    }
 
-        cells.push((1, 2));
-        cells.push((5, 2));
-        cells.push((7, 2));
-        cells.push((11, 2));
-        cells.push((1, 3));
-        cells.push((5, 3));
-        cells.push((7, 3));
-        cells.push((11, 3));
-        cells.push((1, 4));
-        cells.push((5, 4));
-        cells.push((7, 4));
-        cells.push((11, 4));
-
-        for x in 2..=4 {
-            cells.push((x, 5));
-        }
-        for x in 8..=10 {
-            cells.push((x, 5));
-        }
-        for x in 2..=4 {
-            cells.push((x, 7));
-        }
-        for x in 8..=10 {
-            cells.push((x, 7));
-        }
+    pub fn rotated(self, quarter_turns: u32) -> Self {
+        self.transformed(match quarter_turns % 4 {
+            1 => Transform::Rotate90,
+            2 => Transform::Rotate180,
+            3 => Transform::Rotate270,
+            _ => Transform::Identity,
+        })
+    }
 
-        cells.push((1, 8));
-        cells.push((5, 8));
-        cells.push((7, 8));
-        cells.push((11, 8));
-        cells.push((1, 9));
-        cells.push((5, 9));
-        cells.push((7, 9));
-        cells.push((11, 9));
-        cells.push((1, 10));
-        cells.push((5, 10));
-        cells.push((7, 10));
-        cells.push((11, 10));
-
-        for x in 2..=4 {
-            cells.push((x, 12));
-        }
-        for x in 8..=10 {
-            cells.push((x, 12));
+    pub fn flipped_x(self) -> Self {
+        self.transformed(Transform::FlipX)
+    }
+
+    pub fn flipped_y(self) -> Self {
+        self.transformed(Transform::FlipY)
+    }
+
+    pub fn translated(mut self, dx: i32, dy: i32) -> Self {
+        for cell in &mut self.cells {
+            cell.0 += dx;
+            cell.1 += dy;
         }
+        self
+    }
 
-        Pattern {
-            cells,
-            name: "Pulsar",
+    // Moves the bounding box to the origin.
+    pub fn normalized(mut self) -> Self {
+        if let Some((min_x, min_y, _, _)) = self.bounds() {
+            for cell in &mut self.cells {
+                cell.0 -= min_x;
+                cell.1 -= min_y;
+            }
         }
+        self
     }
 
-    pub fn gospel_glider_gun() -> Self {
-        let cells = vec![
-            (0, 4),
-            (0, 5),
-            (1, 4),
-            (1, 5),
-            (10, 4),
-            (10, 5),
-            (10, 6),
-            (11, 3),
-            (11, 7),
-            (12, 2),
-            (12, 8),
-            (13, 2),
-            (13, 8),
-            (14, 5),
-            (15, 3),
-            (15, 7),
-            (16, 4),
-            (16, 5),
-            (16, 6),
-            (17, 5),
-            (20, 2),
-            (20, 3),
-            (20, 4),
-            (21, 2),
-            (21, 3),
-            (21, 4),
-            (22, 1),
-            (22, 5),
-            (24, 0),
-            (24, 1),
-            (24, 5),
-            (24, 6),
-            (34, 2),
-            (34, 3),
-            (35, 2),
-            (35, 3),
-        ];
+    // Pattern files list rows top to bottom, but the renderer draws grid row
+    // 0 at the bottom of the screen. Returns the cells in grid coordinates,
+    // flipped so the pattern shows upright, with the bounding box's
+    // bottom-left corner at (x, y).
+    pub fn to_grid(self, x: i32, y: i32) -> Self {
+        self.flipped_y().normalized().translated(x, y)
+    }
 
-        Pattern {
-            cells,
-            name: "Gosper Glider Gun",
-        }
+    // The inverse of `to_grid` for a whole grid of one byte per cell: the top
+    // row on screen becomes the pattern's first row.
+    pub fn from_grid(cells: &[u8], width: u32) -> Self {
+        let positions = cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| **cell != 0)
+            .map(|(index, _)| ((index as u32 % width) as i32, (index as u32 / width) as i32))
+            .collect();
+        Pattern::new("", positions).flipped_y().normalized()
     }
 
-    pub fn scaled_for_grid(&self, grid_width: u32, grid_height: u32) -> Vec<(u32, u32)> {
-        let center_x = grid_width / 2;
-        let center_y = grid_height / 2;
-
-        let min_dimension = grid_width.min(grid_height);
-
-        if min_dimension >= 200 {
-            match self.name {
-                "Glider" => {
-                    let mut positions = Vec::new();
-                    let offsets = [(-30, -30), (30, -30), (-30, 30), (30, 30)];
-
-                    for (offset_x, offset_y) in offsets.iter() {
-                        for (dx, dy) in &self.cells {
-                            let x = center_x as i32 + dx + offset_x;
-                            let y = center_y as i32 + dy + offset_y;
-
-                            if x >= 0
-                                && y >= 0
-                                && (x as u32) < grid_width
-                                && (y as u32) < grid_height
-                            {
-                                positions.push((x as u32, y as u32));
-                            }
-                        }
-                    }
-                    return positions;
-                }
-                "Gosper Glider Gun" => {
-                    return self
-                        .cells
-                        .iter()
-                        .filter_map(|(dx, dy)| {
-                            let x = center_x as i32 + dx;
-                            let y = center_y as i32 + dy;
-
-                            if x >= 0
-                                && y >= 0
-                                && (x as u32) < grid_width
-                                && (y as u32) < grid_height
-                            {
-                                Some((x as u32, y as u32))
-                            } else {
-                                None
-                            }
-                        })
-                        .collect();
-                }
-                _ => {}
+    // The cell changes that placing this pattern, already in grid
+    // coordinates, at (x, y) would make to `cells`, a grid of one byte per
+    // cell. Cells falling outside the grid are dropped.
+    pub fn placement(
+        &self,
+        x: i32,
+        y: i32,
+        mode: PlaceMode,
+        cells: &[u8],
+        grid_width: u32,
+        grid_height: u32,
+    ) -> Vec<(u32, u32, u8)> {
+        let pattern = self.clone().normalized();
+        let Some((_, _, max_x, max_y)) = pattern.bounds() else {
+            return Vec::new();
+        };
+
+        let (width, height) = (max_x as usize + 1, max_y as usize + 1);
+        let mut stamp = vec![false; width * height];
+        for &(dx, dy) in &pattern.cells {
+            stamp[dy as usize * width + dx as usize] = true;
+        }
+
+        let area: Vec<(i32, i32)> = match mode {
+            PlaceMode::Or | PlaceMode::Xor => pattern.cells.clone(),
+            PlaceMode::Overwrite | PlaceMode::And => (0..height as i32)
+                .flat_map(|dy| (0..width as i32).map(move |dx| (dx, dy)))
+                .collect(),
+        };
+
+        let mut changes = Vec::new();
+        for (dx, dy) in area {
+            let (cell_x, cell_y) = (x + dx, y + dy);
+            if cell_x < 0
+                || cell_y < 0
+                || cell_x as u32 >= grid_width
+                || cell_y as u32 >= grid_height
+            {
+                continue;
+            }
+
+            let index = (cell_y as u32 * grid_width + cell_x as u32) as usize;
+            let current = cells[index] != 0;
+            let alive = mode.combine(current, stamp[dy as usize * width + dx as usize]);
+            if alive != current {
+                changes.push((cell_x as u32, cell_y as u32, alive as u8));
             }
         }
+        changes
+    }
 
+    pub fn positions_at(
+        &self,
+        origin_x: i32,
+        origin_y: i32,
+        grid_width: u32,
+        grid_height: u32,
+    ) -> Vec<(u32, u32)> {
         self.cells
             .iter()
             .filter_map(|(dx, dy)| {
-                let x = center_x as i32 + dx;
-                let y = center_y as i32 + dy;
+                let x = origin_x + dx;
+                let y = origin_y + dy;
 
                 if x >= 0 && y >= 0 && (x as u32) < grid_width && (y as u32) < grid_height {
                     Some((x as u32, y as u32))
@@ -225,3 +291,171 @@ impl Pattern {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::Camera;
+    use crate::cpu::CpuSimulator;
+    use crate::simulator::Simulator;
+
+    fn sorted(mut cells: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+        cells.sort_unstable();
+        cells
+    }
+
+    #[test]
+    fn files_show_the_same_way_up_as_on_lifewiki() {
+        // Heads down and to the right, as drawn in the file.
+        let (glider, _) = rle::parse("x = 3, y = 3\nbo$2bo$3o!").unwrap();
+        let (width, height) = (16, 16);
+        let mut cells = vec![0u8; (width * height) as usize];
+        for (x, y) in glider
+            .clone()
+            .to_grid(4, 8)
+            .positions_at(0, 0, width, height)
+        {
+            cells[(y * width + x) as usize] = 1;
+        }
+
+        // The file's top row lands on the highest grid row, which is drawn
+        // nearest the top of the screen.
+        let index = |x: u32, y: u32| (y * width + x) as usize;
+        assert_eq!(cells[index(5, 10)], 1);
+        assert_eq!(cells[index(4, 8)], 1);
+        let camera = Camera::new();
+        let screen_y = |y| camera.cell_to_screen(0, y, 160.0, 160.0, width, height).1;
+        assert!(screen_y(10) < screen_y(8));
+
+        let mut simulator = CpuSimulator::new(width, height);
+        simulator.load(&cells).unwrap();
+        simulator.step_n(4).unwrap();
+        let cells = simulator.read_cells().unwrap();
+
+        // One cell right and one row further down the screen.
+        assert_eq!(cells[index(6, 9)], 1);
+        assert_eq!(cells[index(5, 10)], 0);
+        let read = Pattern::from_grid(&cells, width);
+        assert_eq!(sorted(read.cells.clone()), sorted(glider.cells));
+        assert_eq!(
+            rle::write(&read, "B3/S23"),
+            "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n"
+        );
+    }
+
+    // An L tromino with a tail, so every symmetry gives a different shape.
+    fn l_shape() -> Pattern {
+        Pattern::new("", vec![(0, 0), (0, 1), (0, 2), (1, 2), (2, 1)])
+    }
+
+    fn shape(pattern: Pattern) -> Vec<(i32, i32)> {
+        sorted(pattern.normalized().cells)
+    }
+
+    #[test]
+    fn the_eight_symmetries_are_distinct() {
+        let expected = [
+            (Transform::Identity, (1, 2)),
+            (Transform::Rotate90, (-2, 1)),
+            (Transform::Rotate180, (-1, -2)),
+            (Transform::Rotate270, (2, -1)),
+            (Transform::FlipX, (-1, 2)),
+            (Transform::FlipY, (1, -2)),
+            (Transform::FlipDiagonal, (2, 1)),
+            (Transform::FlipAntiDiagonal, (-2, -1)),
+        ];
+        for (transform, cell) in expected {
+            assert_eq!(transform.apply(1, 2), cell, "{:?}", transform);
+        }
+
+        let mut shapes: Vec<_> = Transform::ALL
+            .iter()
+            .map(|&transform| shape(l_shape().transformed(transform)))
+            .collect();
+        shapes.sort();
+        shapes.dedup();
+        assert_eq!(shapes.len(), 8);
+    }
+
+    #[test]
+    fn rotations_compose() {
+        let original = shape(l_shape());
+        let mut turned = l_shape();
+        for turns in 1..=4 {
+            turned = turned.transformed(Transform::Rotate90);
+            assert_eq!(shape(turned.clone()), shape(l_shape().rotated(turns)));
+        }
+        assert_eq!(shape(turned), original);
+        assert_eq!(shape(l_shape().rotated(1).rotated(3)), original);
+        for flip in [
+            Transform::FlipX,
+            Transform::FlipY,
+            Transform::FlipDiagonal,
+            Transform::FlipAntiDiagonal,
+        ] {
+            assert_eq!(
+                shape(l_shape().transformed(flip).transformed(flip)),
+                original
+            );
+        }
+        assert_eq!(
+            shape(l_shape().flipped_x().flipped_y()),
+            shape(l_shape().rotated(2))
+        );
+    }
+
+    #[test]
+    fn rotate90_turns_clockwise_on_screen() {
+        // A horizontal bar with a cell below its right end, as in a file.
+        let (pattern, _) = rle::parse("x = 3, y = 2\n3o$2bo!").unwrap();
+        let turned = pattern.transformed(Transform::Rotate90).to_grid(0, 0);
+        // Clockwise, the bar stands upright and the extra cell ends up to the
+        // left of its bottom end. Grid row 0 is the bottom of the screen.
+        assert_eq!(sorted(turned.cells), [(0, 0), (1, 0), (1, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn translating_and_normalizing_move_the_bounding_box() {
+        let moved = l_shape().translated(-7, 4);
+        assert_eq!(moved.bounds(), Some((-7, 4, -5, 6)));
+        assert_eq!(moved.normalized().bounds(), Some((0, 0, 2, 2)));
+        assert_eq!(shape(l_shape().translated(3, 3)), shape(l_shape()));
+
+        let empty = Pattern::new("", Vec::new()).translated(5, 5).normalized();
+        assert_eq!(empty.bounds(), None);
+        assert_eq!((empty.width(), empty.height()), (0, 0));
+    }
+
+    #[test]
+    fn place_modes_act_on_the_box_or_the_live_cells() {
+        // A diagonal pair placed at (1, 1) on a 4x4 grid whose cells (1, 1)
+        // and (2, 1) are alive.
+        let pattern = Pattern::new("", vec![(0, 0), (1, 1)]);
+        let mut cells = vec![0u8; 16];
+        cells[5] = 1;
+        cells[6] = 1;
+        let changes = |mode| {
+            let mut changes = pattern.placement(1, 1, mode, &cells, 4, 4);
+            changes.sort_unstable();
+            changes
+        };
+
+        // Overwrite and And cover the whole 2x2 box, dead cells included.
+        assert_eq!(changes(PlaceMode::Overwrite), [(2, 1, 0), (2, 2, 1)]);
+        assert_eq!(changes(PlaceMode::And), [(2, 1, 0)]);
+        // Or and Xor only touch the pattern's live cells.
+        assert_eq!(changes(PlaceMode::Or), [(2, 2, 1)]);
+        assert_eq!(changes(PlaceMode::Xor), [(1, 1, 0), (2, 2, 1)]);
+
+        // Cells off the grid are dropped.
+        assert_eq!(
+            pattern.placement(3, 3, PlaceMode::Or, &cells, 4, 4),
+            [(3, 3, 1)]
+        );
+        assert!(
+            pattern
+                .placement(-2, 0, PlaceMode::Overwrite, &cells, 4, 4)
+                .is_empty()
+        );
+    }
+}
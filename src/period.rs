@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use wasm_bindgen::prelude::*;
+
+// Generations remembered while looking for a repeat; longer transients are
+// not detected.
+pub const MAX_GENERATIONS: u32 = 1 << 16;
+
+// What a repeating grid does: from generation `stable_after` on, every
+// `period` generations it returns to the same state moved by (dx, dy).
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Periodicity {
+    pub stable_after: u32,
+    pub period: u32,
+    pub dx: i32,
+    pub dy: i32,
+    // Life notation such as "c/4 diagonal", empty when nothing moves.
+    pub speed: String,
+}
+
+// Hashes each generation's live cells relative to their bounding box, so a
+// state that comes back anywhere on the grid is recognised.
+#[derive(Default)]
+pub struct PeriodDetector {
+    // Shape hash -> (generation, bounding box corner) of its first sighting.
+    seen: HashMap<u64, (u32, i32, i32)>,
+    generation: u32,
+    found: Option<Periodicity>,
+}
+
+impl PeriodDetector {
+    pub fn new() -> Self {
+        PeriodDetector::default()
+    }
+
+    pub fn reset(&mut self) {
+        self.seen.clear();
+        self.generation = 0;
+        self.found = None;
+    }
+
+    // Records the next generation. Displacements are reduced modulo the grid
+    // size so spaceships crossing the edge of a torus are still followed.
+    pub fn observe(&mut self, cells: &[u8], width: u32, height: u32) -> Option<&Periodicity> {
+        let generation = self.generation;
+        self.generation += 1;
+        if self.found.is_some() {
+            return self.found.as_ref();
+        }
+
+        let (hash, x, y) = shape_hash(cells, width, height);
+        match self.seen.get(&hash) {
+            Some(&(first, first_x, first_y)) => {
+                let period = generation - first;
+                let dx = wrap(x - first_x, width);
+                let dy = wrap(y - first_y, height);
+                self.found = Some(Periodicity {
+                    stable_after: first,
+                    period,
+                    dx,
+                    dy,
+                    speed: speed(dx, dy, period),
+                });
+                self.seen.clear();
+            }
+            None if generation < MAX_GENERATIONS => {
+                self.seen.insert(hash, (generation, x, y));
+            }
+            None => {}
+        }
+        self.found.as_ref()
+    }
+
+    pub fn result(&self) -> Option<&Periodicity> {
+        self.found.as_ref()
+    }
+
+    // Generations observed since the last reset.
+    pub fn generations(&self) -> u32 {
+        self.generation
+    }
+}
+
+// Hash of the live cells relative to their bounding box, and the box corner.
+fn shape_hash(cells: &[u8], width: u32, height: u32) -> (u64, i32, i32) {
+    let mut hasher = DefaultHasher::new();
+    if width == 0 || height == 0 {
+        return (hasher.finish(), 0, 0);
+    }
+
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (width, height, 0, 0);
+    let mut empty = true;
+    for y in 0..height {
+        let row = &cells[(y * width) as usize..((y + 1) * width) as usize];
+        for (x, _) in row.iter().enumerate().filter(|(_, cell)| **cell != 0) {
+            empty = false;
+            min_x = min_x.min(x as u32);
+            max_x = max_x.max(x as u32);
+            min_y = min_y.min(y);
+            max_y = max_y.max(y);
+        }
+    }
+
+    if empty {
+        return (hasher.finish(), 0, 0);
+    }
+    (max_x - min_x, max_y - min_y).hash(&mut hasher);
+    for y in min_y..=max_y {
+        let start = (y * width + min_x) as usize;
+        let end = (y * width + max_x) as usize + 1;
+        for cell in &cells[start..end] {
+            (*cell != 0).hash(&mut hasher);
+        }
+    }
+    (hasher.finish(), min_x as i32, min_y as i32)
+}
+
+// Picks the shortest way round for a displacement on a grid of `size` cells.
+fn wrap(delta: i32, size: u32) -> i32 {
+    if size == 0 {
+        return delta;
+    }
+    let size = size as i32;
+    let delta = delta.rem_euclid(size);
+    if delta > size / 2 {
+        delta - size
+    } else {
+        delta
+    }
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+// Speed in Life notation: "c/2 orthogonal", "2c/7 orthogonal", "c/4 diagonal"
+// or "(2,1)c/6" for oblique ships.
+pub fn speed(dx: i32, dy: i32, period: u32) -> String {
+    let (dx, dy) = (dx.unsigned_abs(), dy.unsigned_abs());
+    if dx == 0 && dy == 0 {
+        return String::new();
+    }
+    if dx != 0 && dy != 0 && dx != dy {
+        return format!("({},{})c/{}", dx.max(dy), dx.min(dy), period);
+    }
+
+    let distance = dx.max(dy);
+    let divisor = gcd(distance, period);
+    let (distance, period) = (distance / divisor, period / divisor);
+    let direction = if dx == dy { "diagonal" } else { "orthogonal" };
+    match distance {
+        1 => format!("c/{} {}", period, direction),
+        _ => format!("{}c/{} {}", distance, period, direction),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(width: u32, height: u32, live: &[(u32, u32)]) -> Vec<u8> {
+        let mut cells = vec![0; (width * height) as usize];
+        for &(x, y) in live {
+            cells[(y * width + x) as usize] = 1;
+        }
+        cells
+    }
+
+    #[test]
+    fn speeds_use_life_notation() {
+        assert_eq!(speed(1, 1, 4), "c/4 diagonal");
+        assert_eq!(speed(0, -2, 4), "c/2 orthogonal");
+        assert_eq!(speed(2, 0, 7), "2c/7 orthogonal");
+        assert_eq!(speed(-1, 2, 6), "(2,1)c/6");
+        assert_eq!(speed(0, 0, 3), "");
+    }
+
+    #[test]
+    fn empty_and_zero_sized_grids_are_still_lifes() {
+        let mut detector = PeriodDetector::new();
+        assert_eq!(detector.observe(&[], 0, 0), None);
+        assert_eq!(
+            detector.observe(&[], 0, 0).map(|found| found.period),
+            Some(1)
+        );
+
+        let mut detector = PeriodDetector::new();
+        let cells = grid(4, 4, &[]);
+        detector.observe(&cells, 4, 4);
+        assert_eq!(
+            detector.observe(&cells, 4, 4).map(|found| found.period),
+            Some(1)
+        );
+    }
+
+    // One B3/S23 generation on a torus.
+    fn step(cells: &[u8], width: u32, height: u32) -> Vec<u8> {
+        let mut next = vec![0; cells.len()];
+        for y in 0..height {
+            for x in 0..width {
+                let mut neighbours = 0;
+                for (dx, dy) in [(1, 0), (1, 1), (0, 1), (width - 1, 1)] {
+                    for (nx, ny) in [(x + dx, y + dy), (x + width * 2 - dx, y + height * 2 - dy)] {
+                        neighbours += cells[((ny % height) * width + nx % width) as usize];
+                    }
+                }
+                let alive = cells[(y * width + x) as usize] != 0;
+                next[(y * width + x) as usize] =
+                    (neighbours == 3 || (alive && neighbours == 2)) as u8;
+            }
+        }
+        next
+    }
+
+    #[test]
+    fn glider_repeats_moved_one_cell_diagonally() {
+        let live: Vec<_> = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]
+            .iter()
+            .map(|&(x, y)| (x + 2, y + 2))
+            .collect();
+        let mut cells = grid(8, 8, &live);
+        let mut detector = PeriodDetector::new();
+        for _ in 0..4 {
+            assert_eq!(detector.observe(&cells, 8, 8), None);
+            cells = step(&cells, 8, 8);
+        }
+        let found = detector.observe(&cells, 8, 8).cloned().unwrap();
+        assert_eq!((found.stable_after, found.period), (0, 4));
+        assert_eq!((found.dx, found.dy), (1, 1));
+        assert_eq!(found.speed, "c/4 diagonal");
+    }
+}
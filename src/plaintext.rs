@@ -0,0 +1,94 @@
+use crate::patterns::Pattern;
+
+pub fn parse(text: &str) -> Result<Pattern, String> {
+    let mut pattern = Pattern::new("", Vec::new());
+    let mut y = 0;
+
+    for line in text.lines() {
+        let line = line.trim_end();
+
+        if let Some(comment) = line.strip_prefix('!') {
+            let comment = comment.trim();
+            match comment.strip_prefix("Name:") {
+                Some(name) => pattern.name = name.trim().to_string(),
+                None => pattern.comments.push(comment.to_string()),
+            }
+            continue;
+        }
+
+        for (x, c) in line.chars().enumerate() {
+            match c {
+                'O' | '*' => pattern.cells.push((x as i32, y)),
+                '.' => {}
+                _ => return Err(format!("Unexpected character '{}' in plaintext pattern", c)),
+            }
+        }
+        y += 1;
+    }
+
+    Ok(pattern)
+}
+
+pub fn write(pattern: &Pattern) -> String {
+    let mut output = String::new();
+
+    if !pattern.name.is_empty() {
+        output.push_str(&format!("!Name: {}\n", pattern.name));
+    }
+    for comment in &pattern.comments {
+        output.push_str(&format!("!{}\n", comment));
+    }
+
+    let Some((min_x, min_y, max_x, max_y)) = pattern.bounds() else {
+        return output;
+    };
+
+    let width = (max_x - min_x + 1) as usize;
+    let height = (max_y - min_y + 1) as usize;
+    let mut rows = vec![vec!['.'; width]; height];
+    for &(x, y) in &pattern.cells {
+        rows[(y - min_y) as usize][(x - min_x) as usize] = 'O';
+    }
+
+    for row in rows {
+        let line: String = row.into_iter().collect();
+        output.push_str(line.trim_end_matches('.'));
+        output.push('\n');
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bang_lines_hold_the_name_and_comments() {
+        let text = "!Name: Glider\n!The smallest spaceship.\n!\n.O\n..*\nOOO\n";
+        let pattern = parse(text).unwrap();
+        assert_eq!(pattern.name, "Glider");
+        assert_eq!(pattern.comments, ["The smallest spaceship.", ""]);
+        assert_eq!(pattern.cells, [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn blank_lines_are_empty_rows() {
+        let pattern = parse("O\n\n..O").unwrap();
+        assert_eq!(pattern.cells, [(0, 0), (2, 2)]);
+        assert!(parse("O\nX").is_err());
+    }
+
+    #[test]
+    fn written_rows_drop_trailing_dead_cells() {
+        let mut pattern = Pattern::new("Pair", vec![(5, -3), (7, -1)]);
+        pattern.comments.push("Two cells".to_string());
+        let text = write(&pattern);
+        assert_eq!(text, "!Name: Pair\n!Two cells\nO\n\n..O\n");
+
+        let read = parse(&text).unwrap();
+        assert_eq!(read.name, "Pair");
+        assert_eq!(read.comments, ["Two cells"]);
+        assert_eq!(read.cells, [(0, 0), (2, 2)]);
+    }
+}
@@ -0,0 +1,161 @@
+use wasm_bindgen::prelude::*;
+
+use crate::catalog::CATALOG;
+use crate::patterns::Pattern;
+use crate::rle;
+use crate::rule::Rule;
+use crate::topology::Topology;
+
+#[derive(Clone)]
+pub struct RegistryEntry {
+    pub pattern: Pattern,
+    pub category: String,
+    pub description: String,
+    // Rule the pattern was written for, applied when it is loaded. None means
+    // it runs under the current rule; the built-ins all name B3/S23, so
+    // loading one after a pattern from another rule switches back.
+    pub rule: Option<String>,
+}
+
+// Summary of a registry entry for building pattern palettes in JS.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct PatternInfo {
+    pub name: String,
+    pub category: String,
+    pub description: String,
+    pub population: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+// Patterns keyed by name (case-insensitive), kept in registration order.
+#[derive(Clone, Default)]
+pub struct Registry {
+    entries: Vec<RegistryEntry>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    // The embedded catalog, see catalog.rs. Every entry is a Life pattern.
+    pub fn builtin() -> Self {
+        let mut registry = Registry::new();
+        for entry in CATALOG {
+            let (mut pattern, _) = rle::parse(entry.rle).expect("built-in patterns are valid RLE");
+            pattern.name = entry.name.to_string();
+            registry.register(RegistryEntry {
+                pattern,
+                category: entry.category.to_string(),
+                description: entry.description.to_string(),
+                rule: Some(Rule::conway().to_string()),
+            });
+        }
+        registry
+    }
+
+    // Adds an entry, replacing any entry with the same name.
+    pub fn register(&mut self, entry: RegistryEntry) {
+        match self.position(&entry.pattern.name) {
+            Some(index) => self.entries[index] = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    // Registers an RLE pattern. An empty name falls back to the #N line and
+    // the #C comments become the description. A rule in the header must
+    // parse, so a bad one is reported now rather than when the pattern is
+    // loaded.
+    pub fn register_rle(&mut self, name: &str, category: &str, text: &str) -> Result<(), String> {
+        let (mut pattern, rule) = rle::parse(text)?;
+        if let Some(rule) = &rule {
+            match rule.split_once(':') {
+                Some((rule, topology)) => {
+                    Rule::parse(rule)?;
+                    Topology::parse(topology)?;
+                }
+                None => {
+                    Rule::parse(rule)?;
+                }
+            }
+        }
+        if !name.trim().is_empty() {
+            pattern.name = name.trim().to_string();
+        }
+        if pattern.name.is_empty() {
+            return Err("Pattern needs a name".to_string());
+        }
+
+        let description = pattern.comments.join(" ");
+        self.register(RegistryEntry {
+            pattern: pattern.normalized(),
+            category: category.to_string(),
+            description,
+            rule,
+        });
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&RegistryEntry> {
+        self.position(name).map(|index| &self.entries[index])
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<RegistryEntry> {
+        self.position(name).map(|index| self.entries.remove(index))
+    }
+
+    pub fn entries(&self) -> &[RegistryEntry] {
+        &self.entries
+    }
+
+    pub fn infos(&self) -> Vec<PatternInfo> {
+        self.entries
+            .iter()
+            .map(|entry| PatternInfo {
+                name: entry.pattern.name.clone(),
+                category: entry.category.clone(),
+                description: entry.description.clone(),
+                population: entry.pattern.population(),
+                width: entry.pattern.width(),
+                height: entry.pattern.height(),
+            })
+            .collect()
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.pattern.name.eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_rules_are_checked_on_registration() {
+        let mut registry = Registry::new();
+        let bad = registry.register_rle("Bad", "User", "x = 3, y = 1, rule = B9/S23\n3o!");
+        assert!(bad.is_err());
+        assert!(registry.get("Bad").is_none());
+
+        let torus = "x = 3, y = 1, rule = B36/S23:T20,20\n3o!";
+        registry.register_rle("Blinker", "User", torus).unwrap();
+        let rule = registry.get("blinker").unwrap().rule.as_deref();
+        assert_eq!(rule, Some("B36/S23:T20,20"));
+    }
+
+    #[test]
+    fn builtins_switch_back_to_life() {
+        let registry = Registry::builtin();
+        assert!(
+            registry
+                .entries()
+                .iter()
+                .all(|entry| entry.rule.as_deref() == Some("B3/S23"))
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{WebGlBuffer, WebGlProgram, WebGlRenderingContext, WebGlTexture};
+
+use crate::camera::Camera;
+use crate::color::{self, Appearance};
+use crate::shaders;
+use crate::texture::{self, TextureManager};
+use crate::webgl::{self, RenderTarget};
+
+// One canvas showing the simulation. Each view has its own WebGL context, so
+// views other than the one sharing the simulator's context get the cells
+// through `upload` instead of reading the state texture directly.
+pub struct View {
+    gl: WebGlRenderingContext,
+    target: RenderTarget,
+    render_program: WebGlProgram,
+    quad_buffer: WebGlBuffer,
+    display_texture: WebGlTexture,
+    texture_manager: TextureManager,
+    // Version of the cells currently in `display_texture`.
+    uploaded_version: Option<u64>,
+    // Grid size in cells, for drawing grid lines.
+    width: u32,
+    height: u32,
+    pub camera: Camera,
+}
+
+impl View {
+    pub fn new(
+        target: RenderTarget,
+        gl: WebGlRenderingContext,
+        width: u32,
+        height: u32,
+    ) -> Result<View, JsValue> {
+        let render_program = shaders::create_render_program(&gl)?;
+        let quad_buffer = webgl::create_quad_buffer(&gl)?;
+        let display_texture = webgl::create_texture(&gl, width, height)?;
+        let texture_manager = TextureManager::new(gl.clone(), width, height);
+
+        Ok(View {
+            gl,
+            target,
+            render_program,
+            quad_buffer,
+            display_texture,
+            texture_manager,
+            uploaded_version: None,
+            width,
+            height,
+            camera: Camera::new(),
+        })
+    }
+
+    pub fn gl(&self) -> &WebGlRenderingContext {
+        &self.gl
+    }
+
+    pub fn canvas_size(&self) -> (f64, f64) {
+        let (width, height) = self.target.size();
+        (width as f64, height as f64)
+    }
+
+    pub fn needs_upload(&self, version: u64) -> bool {
+        self.uploaded_version != Some(version)
+    }
+
+    pub fn upload(&mut self, cells: &[u8], version: u64) -> Result<(), JsValue> {
+        let data = texture::cells_to_texture_data(cells);
+        self.texture_manager
+            .upload_data(&self.display_texture, &data)?;
+        self.uploaded_version = Some(version);
+        Ok(())
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), JsValue> {
+        self.display_texture = webgl::create_texture(&self.gl, width, height)?;
+        self.texture_manager = TextureManager::new(self.gl.clone(), width, height);
+        self.uploaded_version = None;
+        self.width = width;
+        self.height = height;
+        Ok(())
+    }
+
+    // Draws `state_texture`, or the last uploaded cells when it is None.
+    // Uploaded cells carry no ages, so they are all drawn as just born and
+    // leave no trails.
+    pub fn render(
+        &self,
+        state_texture: Option<&WebGlTexture>,
+        appearance: &Appearance,
+    ) -> Result<(), JsValue> {
+        let state_texture = state_texture.unwrap_or(&self.display_texture);
+
+        self.gl
+            .bind_framebuffer(WebGlRenderingContext::FRAMEBUFFER, None);
+
+        let (canvas_width, canvas_height) = self.target.size();
+
+        self.gl
+            .viewport(0, 0, canvas_width as i32, canvas_height as i32);
+        self.gl.use_program(Some(&self.render_program));
+
+        let background = appearance.palette.background;
+        self.gl
+            .clear_color(background.r, background.g, background.b, 1.0);
+        self.gl.clear(WebGlRenderingContext::COLOR_BUFFER_BIT);
+
+        self.gl.active_texture(WebGlRenderingContext::TEXTURE0);
+        self.gl
+            .bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(state_texture));
+
+        let texture_location = self
+            .gl
+            .get_uniform_location(&self.render_program, "u_texture");
+        if texture_location.is_none() {
+            return Err(JsValue::from_str("u_texture uniform not found"));
+        }
+
+        self.gl.uniform1i(texture_location.as_ref(), 0);
+
+        let offset_location = self
+            .gl
+            .get_uniform_location(&self.render_program, "u_offset");
+        self.gl.uniform2f(
+            offset_location.as_ref(),
+            self.camera.offset_x as f32,
+            self.camera.offset_y as f32,
+        );
+
+        let zoom_location = self.gl.get_uniform_location(&self.render_program, "u_zoom");
+        self.gl
+            .uniform1f(zoom_location.as_ref(), self.camera.zoom as f32);
+
+        self.set_appearance(appearance);
+
+        self.gl
+            .bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&self.quad_buffer));
+        let position_location = self
+            .gl
+            .get_attrib_location(&self.render_program, "a_position");
+        if position_location == -1 {
+            return Err(JsValue::from_str("a_position attribute not found"));
+        }
+
+        self.gl.enable_vertex_attrib_array(position_location as u32);
+        self.gl.vertex_attrib_pointer_with_i32(
+            position_location as u32,
+            2,
+            WebGlRenderingContext::FLOAT,
+            false,
+            0,
+            0,
+        );
+
+        self.gl.draw_arrays(WebGlRenderingContext::TRIANGLES, 0, 6);
+
+        let error = self.gl.get_error();
+        if error != WebGlRenderingContext::NO_ERROR {
+            web_sys::console::log_1(&format!("WebGL error: {}", error).into());
+        }
+
+        Ok(())
+    }
+
+    fn set_appearance(&self, appearance: &Appearance) {
+        let program = &self.render_program;
+
+        let palette = &appearance.palette;
+        let mut states = [0.0f32; color::MAX_STATES * 3];
+        for (state, color) in states.chunks_exact_mut(3).zip(&palette.states) {
+            state.copy_from_slice(&[color.r, color.g, color.b]);
+        }
+        let states_location = self.gl.get_uniform_location(program, "u_stateColors");
+        self.gl
+            .uniform3fv_with_f32_array(states_location.as_ref(), &states);
+
+        let count_location = self.gl.get_uniform_location(program, "u_stateCount");
+        self.gl
+            .uniform1f(count_location.as_ref(), palette.states.len() as f32);
+
+        let background = palette.background;
+        let background_location = self.gl.get_uniform_location(program, "u_background");
+        self.gl.uniform3f(
+            background_location.as_ref(),
+            background.r,
+            background.g,
+            background.b,
+        );
+
+        let grid_lines_location = self.gl.get_uniform_location(program, "u_gridLines");
+        self.gl.uniform1f(
+            grid_lines_location.as_ref(),
+            appearance.grid_lines as u8 as f32,
+        );
+
+        let grid = palette.grid;
+        let grid_color_location = self.gl.get_uniform_location(program, "u_gridColor");
+        self.gl
+            .uniform3f(grid_color_location.as_ref(), grid.r, grid.g, grid.b);
+
+        let grid_size_location = self.gl.get_uniform_location(program, "u_gridSize");
+        self.gl.uniform2f(
+            grid_size_location.as_ref(),
+            self.width as f32,
+            self.height as f32,
+        );
+
+        let (canvas_width, canvas_height) = self.target.size();
+        let canvas_size_location = self.gl.get_uniform_location(program, "u_canvasSize");
+        self.gl.uniform2f(
+            canvas_size_location.as_ref(),
+            canvas_width as f32,
+            canvas_height as f32,
+        );
+
+        let mode_location = self.gl.get_uniform_location(program, "u_colorMode");
+        self.gl
+            .uniform1i(mode_location.as_ref(), appearance.mode as i32);
+
+        let gradient = &appearance.gradient;
+        let mut stops = [0.0f32; color::MAX_GRADIENT_STOPS * 3];
+        for (stop, color) in stops.chunks_exact_mut(3).zip(&gradient.stops) {
+            stop.copy_from_slice(&[color.r, color.g, color.b]);
+        }
+        let gradient_location = self.gl.get_uniform_location(program, "u_gradient");
+        self.gl
+            .uniform3fv_with_f32_array(gradient_location.as_ref(), &stops);
+
+        let stops_location = self.gl.get_uniform_location(program, "u_gradientStops");
+        self.gl
+            .uniform1f(stops_location.as_ref(), gradient.stops.len() as f32);
+
+        let max_age_location = self.gl.get_uniform_location(program, "u_maxAge");
+        self.gl
+            .uniform1f(max_age_location.as_ref(), gradient.max_age as f32);
+
+        let trail_length_location = self.gl.get_uniform_location(program, "u_trailLength");
+        self.gl.uniform1f(
+            trail_length_location.as_ref(),
+            appearance.trail_length as f32,
+        );
+
+        let trail = appearance.trail_color;
+        let trail_color_location = self.gl.get_uniform_location(program, "u_trailColor");
+        self.gl
+            .uniform3f(trail_color_location.as_ref(), trail.r, trail.g, trail.b);
+    }
+}
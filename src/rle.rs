@@ -0,0 +1,316 @@
+use crate::patterns::Pattern;
+
+const MAX_LINE_LENGTH: usize = 70;
+
+// Bounds on patterns whose header gives no size, so a run such as
+// "999999999o" is rejected instead of filling memory.
+const MAX_SIZE: u32 = 1 << 16;
+
+// The rule is returned as written, including any topology suffix, so the
+// caller can validate and apply it. Live cells must lie inside the header's
+// `x` by `y` box.
+pub fn parse(text: &str) -> Result<(Pattern, Option<String>), String> {
+    let mut pattern = Pattern::new("", Vec::new());
+    let mut rule = None;
+    let mut header_seen = false;
+    let (mut width, mut height) = (MAX_SIZE, MAX_SIZE);
+
+    let mut x = 0u32;
+    let mut y = 0u32;
+    let mut run = String::new();
+    let mut finished = false;
+
+    for line in text.lines() {
+        let line = line.trim();
+
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut chars = comment.chars();
+            let tag = chars.next();
+            let value = chars.as_str().trim();
+            match tag {
+                Some('N') => pattern.name = value.to_string(),
+                Some('C') | Some('c') | Some('O') => pattern.comments.push(value.to_string()),
+                Some('r') => rule = Some(value.to_string()),
+                _ => {}
+            }
+            continue;
+        }
+
+        if line.is_empty() || finished {
+            continue;
+        }
+
+        if !header_seen && line.starts_with('x') {
+            header_seen = true;
+            let header = parse_header(line)?;
+            width = header.width.unwrap_or(width);
+            height = header.height.unwrap_or(height);
+            rule = header.rule.or(rule);
+            continue;
+        }
+
+        for c in line.chars() {
+            match c {
+                '0'..='9' => run.push(c),
+                c if c.is_whitespace() => {}
+                _ => {
+                    let count = if run.is_empty() {
+                        1
+                    } else {
+                        run.parse::<u32>()
+                            .map_err(|_| format!("Invalid run count \"{}\"", run))?
+                    };
+                    run.clear();
+
+                    let outside = || {
+                        format!(
+                            "Run \"{}{}\" goes outside the {}x{} pattern",
+                            count, c, width, height
+                        )
+                    };
+                    match c {
+                        'b' | '.' => x = x.checked_add(count).ok_or_else(outside)?,
+                        '$' => {
+                            x = 0;
+                            y = y.checked_add(count).ok_or_else(outside)?;
+                        }
+                        '!' => {
+                            finished = true;
+                            break;
+                        }
+                        c if c.is_ascii_alphabetic() => {
+                            let end = x.checked_add(count).filter(|&end| end <= width);
+                            let Some(end) = end.filter(|_| y < height) else {
+                                return Err(outside());
+                            };
+                            pattern.cells.extend((x..end).map(|x| (x as i32, y as i32)));
+                            x = end;
+                        }
+                        _ => return Err(format!("Unexpected character '{}' in RLE data", c)),
+                    }
+                }
+            }
+        }
+    }
+
+    Ok((pattern, rule))
+}
+
+struct Header {
+    width: Option<u32>,
+    height: Option<u32>,
+    rule: Option<String>,
+}
+
+fn parse_header(line: &str) -> Result<Header, String> {
+    let mut header = Header {
+        width: None,
+        height: None,
+        rule: None,
+    };
+
+    // The rule is always last and may itself contain commas (e.g. ":T100,100").
+    let (sizes, rule_entry) = match line.find("rule") {
+        Some(index) => (&line[..index], Some(&line[index..])),
+        None => (line, None),
+    };
+
+    if let Some(entry) = rule_entry {
+        let (_, value) = entry
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid RLE header \"{}\"", line))?;
+        header.rule = Some(value.trim().to_string());
+    }
+
+    for entry in sizes.split(',').filter(|entry| !entry.trim().is_empty()) {
+        let (key, value) = entry
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid RLE header \"{}\"", line))?;
+        let value = value.trim();
+
+        let size = match key.trim() {
+            "x" => &mut header.width,
+            "y" => &mut header.height,
+            _ => continue,
+        };
+        let parsed = value
+            .parse::<u32>()
+            .ok()
+            .filter(|&size| size <= i32::MAX as u32)
+            .ok_or_else(|| format!("Invalid pattern size \"{}\"", value))?;
+        *size = Some(parsed);
+    }
+
+    Ok(header)
+}
+
+pub fn write(pattern: &Pattern, rule: &str) -> String {
+    let mut output = String::new();
+
+    if !pattern.name.is_empty() {
+        output.push_str(&format!("#N {}\n", pattern.name));
+    }
+    for comment in &pattern.comments {
+        output.push_str(&format!("#C {}\n", comment));
+    }
+
+    let Some((min_x, min_y, max_x, max_y)) = pattern.bounds() else {
+        output.push_str(&format!("x = 0, y = 0, rule = {}\n!\n", rule));
+        return output;
+    };
+
+    let width = (max_x - min_x + 1) as usize;
+    let height = (max_y - min_y + 1) as usize;
+    output.push_str(&format!("x = {}, y = {}, rule = {}\n", width, height, rule));
+
+    let mut grid = vec![false; width * height];
+    for &(x, y) in &pattern.cells {
+        grid[(y - min_y) as usize * width + (x - min_x) as usize] = true;
+    }
+
+    let mut tokens = Vec::new();
+    let mut pending_rows = 0;
+    for row in grid.chunks(width) {
+        let mut runs: Vec<(bool, usize)> = Vec::new();
+        for &alive in row {
+            match runs.last_mut() {
+                Some((state, count)) if *state == alive => *count += 1,
+                _ => runs.push((alive, 1)),
+            }
+        }
+        if let Some((false, _)) = runs.last() {
+            runs.pop();
+        }
+
+        if runs.is_empty() {
+            pending_rows += 1;
+            continue;
+        }
+
+        if !tokens.is_empty() {
+            tokens.push(run_token(pending_rows + 1, '$'));
+        }
+        pending_rows = 0;
+
+        for (alive, count) in runs {
+            tokens.push(run_token(count, if alive { 'o' } else { 'b' }));
+        }
+    }
+    tokens.push("!".to_string());
+
+    let mut line_length = 0;
+    for token in tokens {
+        if line_length + token.len() > MAX_LINE_LENGTH {
+            output.push('\n');
+            line_length = 0;
+        }
+        line_length += token.len();
+        output.push_str(&token);
+    }
+    output.push('\n');
+
+    output
+}
+
+fn run_token(count: usize, tag: char) -> String {
+    if count == 1 {
+        tag.to_string()
+    } else {
+        format!("{}{}", count, tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut cells: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+        cells.sort_unstable_by_key(|&(x, y)| (y, x));
+        cells
+    }
+
+    #[test]
+    fn header_names_and_comments() {
+        let text = "#N Glider\n#C The smallest spaceship.\n#O Richard Guy\n\
+                    x = 3, y = 3, rule = B3/S23:T20,10\nbo$2bo$3o!";
+        let (pattern, rule) = parse(text).unwrap();
+        assert_eq!(pattern.name, "Glider");
+        assert_eq!(pattern.comments, ["The smallest spaceship.", "Richard Guy"]);
+        assert_eq!(rule.as_deref(), Some("B3/S23:T20,10"));
+        assert_eq!(
+            sorted(pattern.cells),
+            [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]
+        );
+
+        let (_, rule) = parse("x = 3, y = 1\n3o!").unwrap();
+        assert_eq!(rule, None);
+        assert!(parse("x = three, y = 1\n3o!").is_err());
+    }
+
+    #[test]
+    fn multi_digit_runs_and_blank_rows() {
+        let (pattern, _) = parse("x = 12, y = 4\n12o3$10bo!").unwrap();
+        assert_eq!(pattern.cells.len(), 13);
+        assert!(pattern.cells.contains(&(11, 0)));
+        assert!(pattern.cells.contains(&(10, 3)));
+        assert!(!pattern.cells.iter().any(|&(_, y)| y == 1 || y == 2));
+    }
+
+    #[test]
+    fn runs_continue_across_lines_and_stop_at_bang() {
+        let (pattern, _) = parse("x = 12, y = 2\n1\n2o$\no!\n5o").unwrap();
+        assert_eq!(pattern.cells.len(), 13);
+        assert!(pattern.cells.contains(&(0, 1)));
+        assert!(parse("x = 1, y = 1\no?!").is_err());
+    }
+
+    #[test]
+    fn runs_stay_inside_the_header_size() {
+        assert!(parse("x = 3, y = 2\n3o$3o!").is_ok());
+        assert!(parse("x = 3, y = 2\n4o!").is_err());
+        assert!(parse("x = 3, y = 2\n2bo$3o$o!").is_err());
+        assert!(parse("x = 3, y = 1\n999999999o!").is_err());
+        // Dead cells and rows past the box are fine while nothing lives there.
+        assert_eq!(parse("x = 3, y = 1\no9b$!").unwrap().0.cells, [(0, 0)]);
+        assert!(parse("x = 3000000000, y = 1\no!").is_err());
+    }
+
+    #[test]
+    fn huge_runs_neither_overflow_nor_fill_memory() {
+        assert!(parse("4294967295b4294967295bo!").is_err());
+        assert!(parse("x = 3, y = 3\n2147483647$2147483647$o!").is_err());
+        assert!(parse("99999999999o!").is_err());
+        // Without a header the pattern may not be wider or taller than MAX_SIZE.
+        assert!(parse(&format!("{}o!", MAX_SIZE + 1)).is_err());
+        assert_eq!(
+            parse(&format!("{}$o!", MAX_SIZE - 1))
+                .unwrap()
+                .0
+                .cells
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn written_lines_wrap_and_read_back() {
+        // Alternating cells give one token per cell, far more than a line holds.
+        let cells: Vec<_> = (0..100).map(|i| (i * 2, i % 3)).collect();
+        let pattern = Pattern::new("Dots", cells.clone());
+        let text = write(&pattern, "B36/S23");
+        assert!(text.lines().all(|line| line.len() <= MAX_LINE_LENGTH));
+        assert!(text.contains("x = 199, y = 3, rule = B36/S23"));
+
+        let (read, rule) = parse(&text).unwrap();
+        assert_eq!(read.name, "Dots");
+        assert_eq!(rule.as_deref(), Some("B36/S23"));
+        assert_eq!(sorted(read.cells), sorted(cells));
+    }
+
+    #[test]
+    fn empty_patterns_write_an_empty_grid() {
+        let text = write(&Pattern::new("", Vec::new()), "B3/S23");
+        assert_eq!(text, "x = 0, y = 0, rule = B3/S23\n!\n");
+        assert!(parse(&text).unwrap().0.cells.is_empty());
+    }
+}
@@ -0,0 +1,145 @@
+use std::fmt;
+
+// Life-like rules stored as bitmasks over the neighbour count (bit n set means
+// n live neighbours triggers a birth / lets a live cell survive).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rule {
+    pub birth: u16,
+    pub survival: u16,
+}
+
+impl Rule {
+    pub fn conway() -> Self {
+        Rule {
+            birth: 1 << 3,
+            survival: (1 << 2) | (1 << 3),
+        }
+    }
+
+    // Accepts "B3/S23", "S23/B3" and the classic survival/birth form "23/3".
+    pub fn parse(rule: &str) -> Result<Self, String> {
+        let rule = rule.trim();
+        let parts: Vec<&str> = rule.split('/').collect();
+        if parts.len() != 2 {
+            return Err(format!("Invalid rule \"{}\": expected B/S notation", rule));
+        }
+
+        let mut birth = None;
+        let mut survival = None;
+
+        for (index, part) in parts.iter().enumerate() {
+            let (kind, digits) = match part.chars().next() {
+                Some('B') | Some('b') => ('B', &part[1..]),
+                Some('S') | Some('s') => ('S', &part[1..]),
+                _ if index == 0 => ('S', *part),
+                _ => ('B', *part),
+            };
+
+            let mask =
+                parse_counts(digits).map_err(|e| format!("Invalid rule \"{}\": {}", rule, e))?;
+
+            let slot = if kind == 'B' {
+                &mut birth
+            } else {
+                &mut survival
+            };
+            if slot.replace(mask).is_some() {
+                return Err(format!("Invalid rule \"{}\": {} given twice", rule, kind));
+            }
+        }
+
+        match (birth, survival) {
+            (Some(birth), Some(survival)) => Ok(Rule { birth, survival }),
+            _ => Err(format!("Invalid rule \"{}\": expected B/S notation", rule)),
+        }
+    }
+
+    pub fn next_state(&self, alive: bool, neighbors: u8) -> bool {
+        let mask = if alive { self.survival } else { self.birth };
+        mask & (1 << neighbors) != 0
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule::conway()
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "B")?;
+        for n in 0..=8 {
+            if self.birth & (1 << n) != 0 {
+                write!(f, "{}", n)?;
+            }
+        }
+        write!(f, "/S")?;
+        for n in 0..=8 {
+            if self.survival & (1 << n) != 0 {
+                write!(f, "{}", n)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_counts(digits: &str) -> Result<u16, String> {
+    let mut mask = 0u16;
+    for c in digits.chars() {
+        match c.to_digit(10) {
+            Some(n) if n <= 8 => mask |= 1 << n,
+            _ => return Err(format!("'{}' is not a neighbour count (0-8)", c)),
+        }
+    }
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn birth_survival_forms_agree() {
+        let life = Rule::conway();
+        for text in ["B3/S23", "b3/s23", "S23/B3", "23/3", " B3/S23 "] {
+            assert_eq!(Rule::parse(text), Ok(life), "{}", text);
+        }
+        let highlife = Rule::parse("B36/S23").unwrap();
+        assert_eq!(highlife.birth, (1 << 3) | (1 << 6));
+        assert_eq!(Rule::parse("B/S012345678").unwrap().birth, 0);
+    }
+
+    #[test]
+    fn bad_rules_are_rejected() {
+        for text in [
+            "B9/S23",
+            "B3/S239",
+            "B3",
+            "B3/S23/S4",
+            "B3/B3",
+            "S23/S23",
+            "Bx/S23",
+            "",
+        ] {
+            assert!(Rule::parse(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for text in ["B3/S23", "B36/S23", "B0/S8", "B/S"] {
+            assert_eq!(Rule::parse(text).unwrap().to_string(), text);
+        }
+        assert_eq!(Rule::parse("S23/B3").unwrap().to_string(), "B3/S23");
+    }
+
+    #[test]
+    fn next_state_follows_the_masks() {
+        let life = Rule::conway();
+        assert!(life.next_state(false, 3));
+        assert!(!life.next_state(false, 2));
+        assert!(life.next_state(true, 2));
+        assert!(!life.next_state(true, 4));
+    }
+}
@@ -1,6 +1,7 @@
 use wasm_bindgen::prelude::*;
 use web_sys::{WebGlProgram, WebGlRenderingContext};
 
+use crate::color;
 use crate::webgl::create_shader;
 
 pub fn create_compute_program(gl: &WebGlRenderingContext) -> Result<WebGlProgram, JsValue> {
@@ -15,79 +16,98 @@ pub fn create_compute_program(gl: &WebGlRenderingContext) -> Result<WebGlProgram
     "#;
 
     let fragment_shader_source = r#"
+        #ifdef GL_FRAGMENT_PRECISION_HIGH
+        precision highp float;
+        #else
         precision mediump float;
+        #endif
         uniform sampler2D u_texture;
         uniform vec2 u_resolution;
+        uniform vec2 u_wrap;
+        uniform vec2 u_twist;
+        uniform float u_birth;
+        uniform float u_survival;
         varying vec2 v_texCoord;
         
-        int getCell(vec2 coord) {
-            vec2 wrappedCoord = fract(coord);
-            vec4 cell = texture2D(u_texture, wrappedCoord);
-            return cell.r > 0.5 ? 1 : 0;
+        bool ruleAllows(float mask, int neighbors) {
+            float bit = 1.0;
+            for (int i = 0; i <= 8; i++) {
+                if (i == neighbors) {
+                    return mod(floor(mask / bit), 2.0) > 0.5;
+                }
+                bit *= 2.0;
+            }
+            return false;
+        }
+        
+        // Edges are resolved here according to the topology rather than by
+        // the sampler, whose wrap mode must stay CLAMP_TO_EDGE for NPOT sizes.
+        int getCell(vec2 cell) {
+            if (cell.x < 0.0 || cell.x >= u_resolution.x) {
+                if (u_wrap.x < 0.5) {
+                    return 0;
+                }
+                cell.x = mod(cell.x, u_resolution.x);
+                if (u_twist.x > 0.5) {
+                    cell.y = u_resolution.y - 1.0 - cell.y;
+                }
+            }
+            if (cell.y < 0.0 || cell.y >= u_resolution.y) {
+                if (u_wrap.y < 0.5) {
+                    return 0;
+                }
+                cell.y = mod(cell.y, u_resolution.y);
+                if (u_twist.y > 0.5) {
+                    cell.x = u_resolution.x - 1.0 - cell.x;
+                }
+            }
+            vec4 state = texture2D(u_texture, (cell + 0.5) / u_resolution);
+            return state.r > 0.5 ? 1 : 0;
+        }
+        
+        // Ages are stored as bytes; see `texture::texel`.
+        float byteAt(float channel) {
+            return floor(channel * 255.0 + 0.5);
         }
         
         void main() {
-            vec2 texelSize = 1.0 / u_resolution;
-            vec2 coord = v_texCoord;
+            vec2 cell = floor(v_texCoord * u_resolution);
             
-            int current = getCell(coord);
+            vec4 state = texture2D(u_texture, (cell + 0.5) / u_resolution);
+            int current = state.r > 0.5 ? 1 : 0;
             
             int neighbors = 0;
-            neighbors += getCell(coord + vec2(-1.0, -1.0) * texelSize);
-            neighbors += getCell(coord + vec2( 0.0, -1.0) * texelSize);
-            neighbors += getCell(coord + vec2( 1.0, -1.0) * texelSize);
-            neighbors += getCell(coord + vec2(-1.0,  0.0) * texelSize);
-            neighbors += getCell(coord + vec2( 1.0,  0.0) * texelSize);
-            neighbors += getCell(coord + vec2(-1.0,  1.0) * texelSize);
-            neighbors += getCell(coord + vec2( 0.0,  1.0) * texelSize);
-            neighbors += getCell(coord + vec2( 1.0,  1.0) * texelSize);
+            neighbors += getCell(cell + vec2(-1.0, -1.0));
+            neighbors += getCell(cell + vec2( 0.0, -1.0));
+            neighbors += getCell(cell + vec2( 1.0, -1.0));
+            neighbors += getCell(cell + vec2(-1.0,  0.0));
+            neighbors += getCell(cell + vec2( 1.0,  0.0));
+            neighbors += getCell(cell + vec2(-1.0,  1.0));
+            neighbors += getCell(cell + vec2( 0.0,  1.0));
+            neighbors += getCell(cell + vec2( 1.0,  1.0));
             
             int newState = 0;
-            if (current == 1) {
-                if (neighbors == 2 || neighbors == 3) {
-                    newState = 1;
-                }
-            } else {
-                if (neighbors == 3) {
-                    newState = 1;
-                }
+            if (ruleAllows(current == 1 ? u_survival : u_birth, neighbors)) {
+                newState = 1;
+            }
+            
+            // G counts generations alive, B generations since death; a B of
+            // 0 on a dead cell means it has not died since it was loaded.
+            float age = 0.0;
+            float sinceDeath = 0.0;
+            if (newState == 1) {
+                age = current == 1 ? min(byteAt(state.g) + 1.0, 255.0) : 1.0;
+            } else if (current == 1) {
+                sinceDeath = 1.0;
+            } else if (byteAt(state.b) > 0.0) {
+                sinceDeath = min(byteAt(state.b) + 1.0, 255.0);
             }
             
-            gl_FragColor = vec4(vec3(float(newState)), 1.0);
+            gl_FragColor = vec4(float(newState), age / 255.0, sinceDeath / 255.0, 1.0);
         }
     "#;
 
-    let vertex_shader = create_shader(
-        gl,
-        WebGlRenderingContext::VERTEX_SHADER,
-        vertex_shader_source,
-    )?;
-    let fragment_shader = create_shader(
-        gl,
-        WebGlRenderingContext::FRAGMENT_SHADER,
-        fragment_shader_source,
-    )?;
-
-    let program = gl
-        .create_program()
-        .ok_or_else(|| JsValue::from_str("Failed to create program"))?;
-
-    gl.attach_shader(&program, &vertex_shader);
-    gl.attach_shader(&program, &fragment_shader);
-    gl.link_program(&program);
-
-    if gl
-        .get_program_parameter(&program, WebGlRenderingContext::LINK_STATUS)
-        .as_bool()
-        .unwrap_or(false)
-    {
-        Ok(program)
-    } else {
-        Err(JsValue::from_str(
-            &gl.get_program_info_log(&program)
-                .unwrap_or_else(|| "Unknown error linking program".into()),
-        ))
-    }
+    create_program(gl, vertex_shader_source, fragment_shader_source)
 }
 
 pub fn create_render_program(gl: &WebGlRenderingContext) -> Result<WebGlProgram, JsValue> {
@@ -102,16 +122,180 @@ pub fn create_render_program(gl: &WebGlRenderingContext) -> Result<WebGlProgram,
     "#;
 
     let fragment_shader_source = r#"
+        #ifdef GL_FRAGMENT_PRECISION_HIGH
+        precision highp float;
+        #else
         precision mediump float;
+        #endif
         uniform sampler2D u_texture;
+        uniform vec2 u_offset;
+        uniform float u_zoom;
+        // Index 0 is dead and 1 alive; states past u_stateCount - 1 use the
+        // last colour.
+        uniform vec3 u_stateColors[MAX_STATES];
+        uniform float u_stateCount;
+        uniform vec3 u_background;
+        // Grid lines are drawn when u_gridLines is 1 and cells are at least
+        // 4 pixels wide.
+        uniform float u_gridLines;
+        uniform vec3 u_gridColor;
+        uniform vec2 u_gridSize;
+        uniform vec2 u_canvasSize;
+        // 0 draws live cells in their state colour, 1 colours them by age.
+        uniform int u_colorMode;
+        uniform vec3 u_gradient[MAX_GRADIENT_STOPS];
+        uniform float u_gradientStops;
+        uniform float u_maxAge;
+        // Dead cells fade from u_trailColor over u_trailLength generations.
+        uniform float u_trailLength;
+        uniform vec3 u_trailColor;
         varying vec2 v_texCoord;
         
+        vec3 stateColor(float state) {
+            vec3 color = u_stateColors[0];
+            for (int i = 1; i < MAX_STATES; i++) {
+                if (float(i) < u_stateCount && float(i) <= state) {
+                    color = u_stateColors[i];
+                }
+            }
+            return color;
+        }
+        
+        vec3 ageColor(float age) {
+            float position = clamp((age - 1.0) / max(u_maxAge - 1.0, 1.0), 0.0, 1.0)
+                * (u_gradientStops - 1.0);
+            vec3 color = u_gradient[0];
+            for (int i = 0; i < MAX_GRADIENT_STOPS - 1; i++) {
+                if (float(i) >= u_gradientStops - 1.0 || position < float(i)) {
+                    break;
+                }
+                color = mix(u_gradient[i], u_gradient[i + 1], min(position - float(i), 1.0));
+            }
+            return color;
+        }
+        
+        void main() {
+            vec2 uv = u_offset + v_texCoord / u_zoom;
+            if (uv.x < 0.0 || uv.y < 0.0 || uv.x >= 1.0 || uv.y >= 1.0) {
+                gl_FragColor = vec4(u_background, 1.0);
+                return;
+            }
+            
+            // Alive is stored as 255, other states as their number.
+            vec4 texel = texture2D(u_texture, uv);
+            float state = floor(texel.r * 255.0 + 0.5);
+            if (state == 255.0) {
+                state = 1.0;
+            }
+            
+            vec3 color = stateColor(state);
+            if (state == 0.0) {
+                float sinceDeath = floor(texel.b * 255.0 + 0.5);
+                if (sinceDeath > 0.0 && sinceDeath <= u_trailLength) {
+                    float trail = 1.0 - (sinceDeath - 1.0) / u_trailLength;
+                    color = mix(color, u_trailColor, trail);
+                }
+            } else if (state == 1.0 && u_colorMode == 1) {
+                color = ageColor(floor(texel.g * 255.0 + 0.5));
+            }
+            
+            if (u_gridLines > 0.5) {
+                vec2 cellPixels = u_canvasSize * u_zoom / u_gridSize;
+                vec2 inCell = fract(uv * u_gridSize) * cellPixels;
+                if (min(cellPixels.x, cellPixels.y) >= 4.0 && (inCell.x < 1.0 || inCell.y < 1.0)) {
+                    color = u_gridColor;
+                }
+            }
+            
+            gl_FragColor = vec4(color, 1.0);
+        }
+    "#;
+
+    let fragment_shader_source = format!(
+        "#define MAX_STATES {}\n#define MAX_GRADIENT_STOPS {}\n{}",
+        color::MAX_STATES,
+        color::MAX_GRADIENT_STOPS,
+        fragment_shader_source
+    );
+    create_program(gl, vertex_shader_source, &fragment_shader_source)
+}
+
+// Sums 8x8 blocks of cells so statistics can be read back from a small
+// texture. Each block writes two texels: (population, births, deaths) and
+// bitmasks of its occupied (columns, rows), all as bytes.
+pub const REDUCTION_BLOCK_SIZE: u32 = 8;
+
+pub fn create_reduction_program(gl: &WebGlRenderingContext) -> Result<WebGlProgram, JsValue> {
+    let vertex_shader_source = r#"
+        attribute vec2 a_position;
+        
+        void main() {
+            gl_Position = vec4(a_position, 0.0, 1.0);
+        }
+    "#;
+
+    let fragment_shader_source = r#"
+        #ifdef GL_FRAGMENT_PRECISION_HIGH
+        precision highp float;
+        #else
+        precision mediump float;
+        #endif
+        uniform sampler2D u_current;
+        uniform sampler2D u_previous;
+        uniform vec2 u_resolution;
+        
+        float cellAt(sampler2D state, vec2 cell) {
+            if (cell.x >= u_resolution.x || cell.y >= u_resolution.y) {
+                return 0.0;
+            }
+            return step(0.5, texture2D(state, (cell + 0.5) / u_resolution).r);
+        }
+        
         void main() {
-            vec4 texColor = texture2D(u_texture, v_texCoord);
-            gl_FragColor = vec4(texColor.rgb, 1.0);
+            vec2 texel = floor(gl_FragCoord.xy);
+            vec2 origin = vec2(floor(texel.x / 2.0), texel.y) * 8.0;
+            
+            float population = 0.0;
+            float births = 0.0;
+            float deaths = 0.0;
+            float columns = 0.0;
+            float rows = 0.0;
+            float bit = 1.0;
+            
+            for (int i = 0; i < 8; i++) {
+                float columnOccupied = 0.0;
+                float rowOccupied = 0.0;
+                for (int j = 0; j < 8; j++) {
+                    vec2 cell = origin + vec2(float(i), float(j));
+                    float now = cellAt(u_current, cell);
+                    float before = cellAt(u_previous, cell);
+                    population += now;
+                    births += now * (1.0 - before);
+                    deaths += before * (1.0 - now);
+                    columnOccupied = max(columnOccupied, now);
+                    rowOccupied = max(rowOccupied, cellAt(u_current, origin + vec2(float(j), float(i))));
+                }
+                columns += columnOccupied * bit;
+                rows += rowOccupied * bit;
+                bit *= 2.0;
+            }
+            
+            if (mod(texel.x, 2.0) < 1.0) {
+                gl_FragColor = vec4(population, births, deaths, 255.0) / 255.0;
+            } else {
+                gl_FragColor = vec4(columns, rows, 0.0, 255.0) / 255.0;
+            }
         }
     "#;
 
+    create_program(gl, vertex_shader_source, fragment_shader_source)
+}
+
+fn create_program(
+    gl: &WebGlRenderingContext,
+    vertex_shader_source: &str,
+    fragment_shader_source: &str,
+) -> Result<WebGlProgram, JsValue> {
     let vertex_shader = create_shader(
         gl,
         WebGlRenderingContext::VERTEX_SHADER,
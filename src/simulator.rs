@@ -0,0 +1,100 @@
+use wasm_bindgen::prelude::*;
+use web_sys::WebGlTexture;
+
+use crate::hashlife::Universe;
+use crate::rule::Rule;
+use crate::stats::Statistics;
+use crate::topology::Topology;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    WebGl,
+    Cpu,
+    HashLife,
+}
+
+// Largest k for `step_pow2` on backends that run every generation; past
+// that a single call would hang the page.
+pub const MAX_GRID_STEP_POW2: u32 = 20;
+
+// Cells are exchanged as one byte per cell in row-major order, 0 = dead and
+// 1 = alive, using the same (x, y) layout as the state textures.
+pub trait Simulator {
+    fn width(&self) -> u32;
+
+    fn height(&self) -> u32;
+
+    fn load(&mut self, cells: &[u8]) -> Result<(), JsValue>;
+
+    fn set_cell(&mut self, x: u32, y: u32, alive: bool) -> Result<(), JsValue>;
+
+    // Callers are expected to keep the region inside the grid.
+    fn read_region(&self, x: u32, y: u32, width: u32, height: u32) -> Result<Vec<u8>, JsValue>;
+
+    fn read_cells(&self) -> Result<Vec<u8>, JsValue> {
+        self.read_region(0, 0, self.width(), self.height())
+    }
+
+    fn statistics(&self) -> Result<Statistics, JsValue>;
+
+    fn set_rule(&mut self, rule: Rule);
+
+    fn set_topology(&mut self, topology: Topology);
+
+    fn step(&mut self) -> Result<(), JsValue>;
+
+    fn step_n(&mut self, generations: u32) -> Result<(), JsValue> {
+        for _ in 0..generations {
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    // Largest k that `step_pow2` accepts. Always below 64, so 2^k
+    // generations fit in a u64.
+    fn max_step_pow2(&self) -> u32 {
+        MAX_GRID_STEP_POW2
+    }
+
+    // Advances 2^k generations. HashLife jumps there directly; the grid
+    // backends run it as batches of `step_n`.
+    fn step_pow2(&mut self, k: u32) -> Result<(), JsValue> {
+        if k > self.max_step_pow2() {
+            return Err(JsValue::from_str(&format!(
+                "Cannot step 2^{} generations at once on this backend; the limit is 2^{}",
+                k,
+                self.max_step_pow2()
+            )));
+        }
+        let mut remaining = 1u64 << k;
+        while remaining > 0 {
+            let batch = remaining.min(u32::MAX as u64) as u32;
+            self.step_n(batch)?;
+            remaining -= batch as u64;
+        }
+        Ok(())
+    }
+
+    // Backends that keep the state in host memory expose it here so the
+    // renderer can upload it; GPU backends return None and provide a texture.
+    fn host_cells(&self) -> Option<&[u8]> {
+        None
+    }
+
+    fn texture(&self) -> Option<&WebGlTexture> {
+        None
+    }
+
+    // Only the HashLife backend keeps a quadtree universe.
+    fn universe(&self) -> Option<&Universe> {
+        None
+    }
+
+    // The universe is in grid coordinates (see `macrocell::to_grid`); grid
+    // backends load its [0, width) x [0, height) window.
+    fn set_universe(&mut self, universe: Universe) -> Result<(), JsValue> {
+        let cells = universe.read_region(0, 0, self.width(), self.height());
+        self.load(&cells)
+    }
+}
@@ -0,0 +1,214 @@
+use crate::hashlife::{Tree, Universe};
+use crate::rule::Rule;
+
+// A compressed copy of the grid. Sparse grids are stored as varint run
+// lengths of alternating dead and live cells; dense ones (random soups) fall
+// back to one bit per cell, whichever is smaller. HashLife universes reach
+// past the grid, so they are kept whole as quadtrees instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    len: usize,
+    encoding: Encoding,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Encoding {
+    Runs(Vec<u8>),
+    Bits(Vec<u8>),
+    // With the grid width, for `to_cells`.
+    Tree(Tree, u32),
+}
+
+impl Snapshot {
+    pub fn from_cells(cells: &[u8]) -> Self {
+        let bits_len = cells.len().div_ceil(8);
+        let encoding = match encode_runs(cells, bits_len) {
+            Some(runs) => Encoding::Runs(runs),
+            None => Encoding::Bits(encode_bits(cells)),
+        };
+
+        Snapshot {
+            len: cells.len(),
+            encoding,
+        }
+    }
+
+    pub fn from_universe(universe: &Universe, width: u32, height: u32) -> Self {
+        Snapshot {
+            len: width as usize * height as usize,
+            encoding: Encoding::Tree(universe.to_tree(), width),
+        }
+    }
+
+    // The universe a HashLife snapshot was taken from, with its generation.
+    pub fn to_universe(&self, rule: Rule) -> Option<Universe> {
+        match &self.encoding {
+            Encoding::Tree(tree, _) => Some(Universe::from_tree(tree, rule)),
+            _ => None,
+        }
+    }
+
+    // The [0, width) x [0, height) window for universe snapshots.
+    pub fn to_cells(&self) -> Vec<u8> {
+        let mut cells = Vec::with_capacity(self.len);
+        match &self.encoding {
+            Encoding::Runs(runs) => {
+                let mut alive = false;
+                let mut bytes = runs.iter();
+                while let Some(run) = read_varint(&mut bytes) {
+                    cells.resize(cells.len() + run, alive as u8);
+                    alive = !alive;
+                }
+                cells.resize(self.len, 0);
+            }
+            Encoding::Bits(bits) => {
+                cells.extend((0..self.len).map(|index| (bits[index / 8] >> (index % 8)) & 1));
+            }
+            Encoding::Tree(tree, width) => {
+                let height = self.len.checked_div(*width as usize).unwrap_or(0) as u32;
+                let universe = Universe::from_tree(tree, Rule::default());
+                cells = universe.read_region(0, 0, *width, height);
+            }
+        }
+        cells
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Bytes held by the encoded cells.
+    pub fn size(&self) -> usize {
+        match &self.encoding {
+            Encoding::Runs(bytes) | Encoding::Bits(bytes) => bytes.len(),
+            Encoding::Tree(tree, _) => tree.size(),
+        }
+    }
+}
+
+// Gives up once the runs would be larger than `limit` bytes.
+fn encode_runs(cells: &[u8], limit: usize) -> Option<Vec<u8>> {
+    let mut runs = Vec::new();
+    let mut alive = false;
+    let mut run = 0;
+
+    for &cell in cells {
+        if (cell != 0) == alive {
+            run += 1;
+            continue;
+        }
+        write_varint(&mut runs, run);
+        if runs.len() > limit {
+            return None;
+        }
+        alive = !alive;
+        run = 1;
+    }
+    // A trailing dead run is implied by the length.
+    if alive {
+        write_varint(&mut runs, run);
+    }
+
+    (runs.len() <= limit).then_some(runs)
+}
+
+fn encode_bits(cells: &[u8]) -> Vec<u8> {
+    let mut bits = vec![0u8; cells.len().div_ceil(8)];
+    for (index, &cell) in cells.iter().enumerate() {
+        if cell != 0 {
+            bits[index / 8] |= 1 << (index % 8);
+        }
+    }
+    bits
+}
+
+fn write_varint(output: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        output.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    output.push(value as u8);
+}
+
+fn read_varint<'a>(bytes: &mut impl Iterator<Item = &'a u8>) -> Option<usize> {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.next()?;
+        value |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+        shift += 7;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sparse_grids_are_stored_as_runs() {
+        // Runs of 300 and 20000 dead cells need two and three varint bytes.
+        let mut cells = vec![0u8; 20_400];
+        cells[300] = 1;
+        cells[301] = 1;
+        cells[20_302] = 1;
+        let snapshot = Snapshot::from_cells(&cells);
+        assert!(matches!(snapshot.encoding, Encoding::Runs(_)));
+        assert_eq!(snapshot.size(), 2 + 1 + 3 + 1);
+        assert_eq!(snapshot.len(), cells.len());
+        assert_eq!(snapshot.to_cells(), cells);
+
+        // A live run at the very end is written out; a dead one is implied.
+        let cells = [0, 1, 1];
+        assert_eq!(Snapshot::from_cells(&cells).to_cells(), cells);
+        let empty = Snapshot::from_cells(&[]);
+        assert!(empty.is_empty());
+        assert!(empty.to_cells().is_empty());
+    }
+
+    #[test]
+    fn dense_grids_fall_back_to_bits() {
+        // Alternating cells give a run per cell, far more than a bit each.
+        let cells: Vec<u8> = (0..1001).map(|index| (index % 2) as u8).collect();
+        let snapshot = Snapshot::from_cells(&cells);
+        assert!(matches!(snapshot.encoding, Encoding::Bits(_)));
+        assert_eq!(snapshot.size(), 126);
+        assert_eq!(snapshot.to_cells(), cells);
+    }
+
+    #[test]
+    fn varints_round_trip() {
+        for value in [0, 1, 127, 128, 300, 16_383, 16_384, usize::MAX >> 1] {
+            let mut bytes = Vec::new();
+            write_varint(&mut bytes, value);
+            assert_eq!(read_varint(&mut bytes.iter()), Some(value));
+        }
+        assert_eq!(read_varint(&mut [0x80u8].iter()), None);
+    }
+
+    #[test]
+    fn universes_are_kept_whole() {
+        let mut universe = Universe::new();
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2), (-500, 900)] {
+            universe.set_cell(x, y, true).unwrap();
+        }
+        universe.set_generation(12);
+        let snapshot = Snapshot::from_universe(&universe, 4, 3);
+
+        let restored = snapshot.to_universe(Rule::default()).unwrap();
+        assert_eq!(restored.generation(), 12);
+        assert_eq!(restored.population(), 6);
+        assert_eq!(snapshot.to_cells(), [0, 1, 0, 0, 0, 0, 1, 0, 1, 1, 1, 0]);
+        assert!(
+            Snapshot::from_cells(&[1])
+                .to_universe(Rule::default())
+                .is_none()
+        );
+    }
+}
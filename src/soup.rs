@@ -0,0 +1,231 @@
+use wasm_bindgen::prelude::*;
+
+// Symmetries for random soups, named as in apgsearch. Soups with C4 or D8
+// symmetry need a square region.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Symmetry {
+    // No symmetry.
+    #[default]
+    C1,
+    // Unchanged by a half turn.
+    C2,
+    // Unchanged by a quarter turn.
+    C4,
+    // Mirrored left and right.
+    D2,
+    // Mirrored left and right, and top and bottom.
+    D4,
+    // Unchanged by all eight symmetries of the square.
+    D8,
+}
+
+impl Symmetry {
+    // Cells of a `width` x `height` region that must match (x, y).
+    fn orbit(&self, x: u32, y: u32, width: u32, height: u32) -> Vec<(u32, u32)> {
+        let (flip_x, flip_y) = (width - 1 - x, height - 1 - y);
+        match self {
+            Symmetry::C1 => vec![(x, y)],
+            Symmetry::C2 => vec![(x, y), (flip_x, flip_y)],
+            Symmetry::C4 => vec![(x, y), (flip_y, x), (flip_x, flip_y), (y, flip_x)],
+            Symmetry::D2 => vec![(x, y), (flip_x, y)],
+            Symmetry::D4 => vec![(x, y), (flip_x, y), (x, flip_y), (flip_x, flip_y)],
+            Symmetry::D8 => vec![
+                (x, y),
+                (flip_y, x),
+                (flip_x, flip_y),
+                (y, flip_x),
+                (flip_x, y),
+                (x, flip_y),
+                (y, x),
+                (flip_y, flip_x),
+            ],
+        }
+    }
+
+    fn needs_square(&self) -> bool {
+        matches!(self, Symmetry::C4 | Symmetry::D8)
+    }
+}
+
+// SplitMix64: small, fast and the same on every platform, so a seed always
+// gives the same soup.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+// One byte per cell for a `width` x `height` soup, row by row. Every cell is
+// drawn in order and then copied from the first cell of its orbit, so the
+// soup depends only on the seed, density, size and symmetry.
+pub fn generate(
+    seed: u64,
+    density: f64,
+    width: u32,
+    height: u32,
+    symmetry: Symmetry,
+) -> Result<Vec<u8>, String> {
+    if !(0.0..=1.0).contains(&density) {
+        return Err(format!("Density {} is not between 0 and 1", density));
+    }
+    if symmetry.needs_square() && width != height {
+        return Err(format!(
+            "{:?} soups need a square region, not {}x{}",
+            symmetry, width, height
+        ));
+    }
+
+    let mut rng = Rng::new(seed);
+    let random: Vec<u8> = (0..width as usize * height as usize)
+        .map(|_| (rng.next_f64() < density) as u8)
+        .collect();
+
+    let mut cells = vec![0u8; random.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let index = symmetry
+                .orbit(x, y, width, height)
+                .into_iter()
+                .map(|(x, y)| (y * width + x) as usize)
+                .min()
+                .unwrap_or_default();
+            cells[(y * width + x) as usize] = random[index];
+        }
+    }
+    Ok(cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cells that must match (x, y) in a width x height soup.
+    type Images = fn(u32, u32, u32, u32) -> Vec<(u32, u32)>;
+
+    fn cell(cells: &[u8], width: u32, x: u32, y: u32) -> u8 {
+        cells[(y * width + x) as usize]
+    }
+
+    #[test]
+    fn the_generator_is_splitmix64() {
+        // The reference outputs for seed 0.
+        let mut rng = Rng::new(0);
+        assert_eq!(rng.next_u64(), 0xe220_a839_7b1d_cdaf);
+        assert_eq!(rng.next_u64(), 0x6e78_9e6a_a1b9_65f4);
+        assert_eq!(rng.next_u64(), 0x06c4_5d18_8009_454f);
+    }
+
+    #[test]
+    fn a_seed_always_gives_the_same_soup() {
+        let soup = generate(2024, 0.5, 8, 4, Symmetry::C1).unwrap();
+        assert_eq!(
+            soup,
+            [
+                0, 1, 1, 1, 0, 0, 1, 0, //
+                1, 1, 1, 0, 0, 0, 1, 0, //
+                0, 1, 0, 1, 0, 0, 1, 1, //
+                0, 0, 1, 0, 1, 1, 0, 1,
+            ]
+        );
+        assert_eq!(generate(2024, 0.5, 8, 4, Symmetry::C1).unwrap(), soup);
+        assert_ne!(generate(2025, 0.5, 8, 4, Symmetry::C1).unwrap(), soup);
+    }
+
+    #[test]
+    fn soups_have_their_symmetry() {
+        let checks: [(Symmetry, Images); 5] = [
+            (Symmetry::C2, |x, y, w, h| vec![(w - 1 - x, h - 1 - y)]),
+            (Symmetry::C4, |x, y, w, _| vec![(w - 1 - y, x)]),
+            (Symmetry::D2, |x, y, w, _| vec![(w - 1 - x, y)]),
+            (Symmetry::D4, |x, y, w, h| {
+                vec![(w - 1 - x, y), (x, h - 1 - y)]
+            }),
+            (Symmetry::D8, |x, y, w, h| {
+                vec![(w - 1 - x, y), (x, h - 1 - y), (y, x)]
+            }),
+        ];
+        for (symmetry, images) in checks {
+            // Odd sizes too, where the middle row or column maps to itself.
+            for (width, height) in [(16, 16), (9, 9), (12, 7)] {
+                if symmetry.needs_square() && width != height {
+                    continue;
+                }
+                let soup = generate(7, 0.4, width, height, symmetry).unwrap();
+                assert!(soup.contains(&1) && soup.contains(&0));
+                for y in 0..height {
+                    for x in 0..width {
+                        for (image_x, image_y) in images(x, y, width, height) {
+                            assert_eq!(
+                                cell(&soup, width, x, y),
+                                cell(&soup, width, image_x, image_y),
+                                "{:?} {}x{} at ({}, {})",
+                                symmetry,
+                                width,
+                                height,
+                                x,
+                                y
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        // C1 soups are not accidentally symmetric.
+        let soup = generate(7, 0.4, 16, 16, Symmetry::C1).unwrap();
+        assert!((0..16).any(|x| cell(&soup, 16, x, 0) != cell(&soup, 16, 15 - x, 0)));
+    }
+
+    #[test]
+    fn density_is_bounded() {
+        assert!(
+            generate(1, 0.0, 10, 10, Symmetry::C1)
+                .unwrap()
+                .iter()
+                .all(|&c| c == 0)
+        );
+        assert!(
+            generate(1, 1.0, 10, 10, Symmetry::D4)
+                .unwrap()
+                .iter()
+                .all(|&c| c == 1)
+        );
+        let soup = generate(1, 0.3, 100, 100, Symmetry::C1).unwrap();
+        let population = soup.iter().filter(|&&c| c == 1).count();
+        assert!((2700..3300).contains(&population), "{}", population);
+
+        assert!(generate(1, -0.1, 10, 10, Symmetry::C1).is_err());
+        assert!(generate(1, 1.5, 10, 10, Symmetry::C1).is_err());
+        assert!(generate(1, f64::NAN, 10, 10, Symmetry::C1).is_err());
+        assert!(generate(1, 0.5, 0, 0, Symmetry::C2).unwrap().is_empty());
+    }
+
+    #[test]
+    fn quarter_turn_symmetries_need_a_square() {
+        for symmetry in [Symmetry::C4, Symmetry::D8] {
+            assert!(generate(1, 0.5, 8, 6, symmetry).is_err());
+            assert!(generate(1, 0.5, 8, 8, symmetry).is_ok());
+        }
+        for symmetry in [Symmetry::C1, Symmetry::C2, Symmetry::D2, Symmetry::D4] {
+            assert!(generate(1, 0.5, 8, 6, symmetry).is_ok());
+        }
+    }
+}
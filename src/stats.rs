@@ -0,0 +1,178 @@
+use wasm_bindgen::prelude::*;
+
+// Births and deaths compare the current cells with the generation before the
+// last step, so edits made since then are counted as well.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Statistics {
+    pub population: u32,
+    pub births: u32,
+    pub deaths: u32,
+    // Inclusive bounding box of the live cells; all zero when the grid is empty.
+    pub min_x: u32,
+    pub min_y: u32,
+    pub max_x: u32,
+    pub max_y: u32,
+    pub density: f64,
+}
+
+impl Statistics {
+    pub fn from_cells(previous: &[u8], current: &[u8], width: u32, height: u32) -> Self {
+        let mut stats = Statistics::default();
+        let mut bounds = Bounds::default();
+
+        for (index, (&before, &now)) in previous.iter().zip(current).enumerate() {
+            let (before, now) = (before != 0, now != 0);
+            if now {
+                stats.population += 1;
+                bounds.include(index as u32 % width, index as u32 / width);
+            }
+            if now && !before {
+                stats.births += 1;
+            }
+            if before && !now {
+                stats.deaths += 1;
+            }
+        }
+
+        stats.finish(bounds, width, height)
+    }
+
+    // Combines per-block results of the GPU reduction pass: each block carries
+    // its counts plus bitmasks of the occupied columns and rows.
+    pub fn from_blocks(
+        blocks: &[Block],
+        blocks_across: u32,
+        block_size: u32,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let mut stats = Statistics::default();
+        let mut bounds = Bounds::default();
+
+        for (index, block) in blocks.iter().enumerate() {
+            stats.population += block.population;
+            stats.births += block.births;
+            stats.deaths += block.deaths;
+
+            if block.columns != 0 && block.rows != 0 {
+                let origin_x = (index as u32 % blocks_across) * block_size;
+                let origin_y = (index as u32 / blocks_across) * block_size;
+                bounds.include(
+                    origin_x + block.columns.trailing_zeros(),
+                    origin_y + block.rows.trailing_zeros(),
+                );
+                bounds.include(
+                    origin_x + 31 - block.columns.leading_zeros(),
+                    origin_y + 31 - block.rows.leading_zeros(),
+                );
+            }
+        }
+
+        stats.finish(bounds, width, height)
+    }
+
+    fn finish(mut self, bounds: Bounds, width: u32, height: u32) -> Self {
+        if let Some((min_x, min_y, max_x, max_y)) = bounds.0 {
+            self.min_x = min_x;
+            self.min_y = min_y;
+            self.max_x = max_x;
+            self.max_y = max_y;
+        }
+        // An empty 0x0 grid has no density; report 0 rather than NaN.
+        let area = width as f64 * height as f64;
+        self.density = if area > 0.0 {
+            self.population as f64 / area
+        } else {
+            0.0
+        };
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Block {
+    pub population: u32,
+    pub births: u32,
+    pub deaths: u32,
+    pub columns: u32,
+    pub rows: u32,
+}
+
+#[derive(Default)]
+struct Bounds(Option<(u32, u32, u32, u32)>);
+
+impl Bounds {
+    fn include(&mut self, x: u32, y: u32) {
+        self.0 = Some(match self.0 {
+            Some((min_x, min_y, max_x, max_y)) => {
+                (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+            }
+            None => (x, y, x, y),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn births_deaths_and_bounds_compare_two_generations() {
+        // A blinker turning from vertical to horizontal on a 5x4 grid.
+        let mut previous = vec![0u8; 20];
+        let mut current = vec![0u8; 20];
+        for index in [2, 7, 12] {
+            previous[index] = 1;
+        }
+        for index in [6, 7, 8] {
+            current[index] = 1;
+        }
+
+        let stats = Statistics::from_cells(&previous, &current, 5, 4);
+        assert_eq!((stats.population, stats.births, stats.deaths), (3, 2, 2));
+        assert_eq!(
+            (stats.min_x, stats.min_y, stats.max_x, stats.max_y),
+            (1, 1, 3, 1)
+        );
+        assert_eq!(stats.density, 3.0 / 20.0);
+    }
+
+    #[test]
+    fn blocks_combine_counts_and_bounds() {
+        // Two 8x8 blocks across a 16x8 grid: cells at (2, 3) and (13, 6).
+        let blocks = [
+            Block {
+                population: 1,
+                births: 1,
+                deaths: 0,
+                columns: 1 << 2,
+                rows: 1 << 3,
+            },
+            Block {
+                population: 1,
+                births: 0,
+                deaths: 2,
+                columns: 1 << 5,
+                rows: 1 << 6,
+            },
+        ];
+        let stats = Statistics::from_blocks(&blocks, 2, 8, 16, 8);
+        assert_eq!((stats.population, stats.births, stats.deaths), (2, 1, 2));
+        assert_eq!(
+            (stats.min_x, stats.min_y, stats.max_x, stats.max_y),
+            (2, 3, 13, 6)
+        );
+        assert_eq!(stats.density, 2.0 / 128.0);
+    }
+
+    #[test]
+    fn empty_grids_have_zero_density() {
+        let stats = Statistics::from_cells(&[], &[], 0, 0);
+        assert_eq!(stats, Statistics::default());
+        assert_eq!(Statistics::from_blocks(&[], 0, 8, 0, 0).density, 0.0);
+
+        let stats = Statistics::from_cells(&[0; 6], &[0; 6], 3, 2);
+        assert_eq!(stats, Statistics::default());
+    }
+}
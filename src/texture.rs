@@ -30,9 +30,7 @@ impl TextureManager {
                 Some(data),
             );
 
-        if let Err(e) = result {
-            return Err(e);
-        }
+        result?;
 
         self.set_texture_parameters();
         self.gl
@@ -42,80 +40,18 @@ impl TextureManager {
     }
 
     pub fn create_pattern_data(&self, positions: &[(u32, u32)]) -> Vec<u8> {
-        let mut data = vec![0u8; (self.width * self.height * 4) as usize];
+        let mut data = texel(false).repeat((self.width * self.height) as usize);
 
         for &(x, y) in positions {
             if x < self.width && y < self.height {
                 let index = ((y * self.width + x) * 4) as usize;
-                data[index] = 255; // R
-                data[index + 1] = 255; // G
-                data[index + 2] = 255; // B
-                data[index + 3] = 255; // A
+                data[index..index + 4].copy_from_slice(&texel(true));
             }
         }
 
         data
     }
 
-    pub fn create_random_data(&self, density: f64) -> Vec<u8> {
-        let mut data = vec![0u8; (self.width * self.height * 4) as usize];
-
-        for y in 0..self.height {
-            for x in 0..self.width {
-                let index = ((y * self.width + x) * 4) as usize;
-
-                let is_alive = js_sys::Math::random() < density;
-                let color = if is_alive { 255u8 } else { 0u8 };
-
-                data[index] = color;
-                data[index + 1] = color;
-                data[index + 2] = color;
-                data[index + 3] = 255;
-            }
-        }
-
-        data
-    }
-
-    pub fn add_cells_in_area(
-        &self,
-        texture: &WebGlTexture,
-        center_x: u32,
-        center_y: u32,
-        radius: u32,
-    ) -> Result<(), JsValue> {
-        self.gl
-            .bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(texture));
-
-        let data = [255u8, 255, 255, 255];
-
-        for dy in -(radius as i32)..=(radius as i32) {
-            for dx in -(radius as i32)..=(radius as i32) {
-                let x = center_x as i32 + dx;
-                let y = center_y as i32 + dy;
-
-                if x >= 0 && y >= 0 && (x as u32) < self.width && (y as u32) < self.height {
-                    if dx * dx + dy * dy <= (radius as i32) * (radius as i32) {
-                        self.gl
-                            .tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_u8_array(
-                                WebGlRenderingContext::TEXTURE_2D,
-                                0,
-                                x,
-                                y,
-                                1,
-                                1,
-                                WebGlRenderingContext::RGBA,
-                                WebGlRenderingContext::UNSIGNED_BYTE,
-                                Some(&data),
-                            )?;
-                    }
-                }
-            }
-        }
-
-        Ok(())
-    }
-
     fn set_texture_parameters(&self) {
         self.gl.tex_parameteri(
             WebGlRenderingContext::TEXTURE_2D,
@@ -139,3 +75,31 @@ impl TextureManager {
         );
     }
 }
+
+// State texels hold the cell in R (0 or 255), the generations it has been
+// alive in G and the generations since it died in B, both counting up to 255.
+// B stays 0 for cells that have not died since they were loaded. Cells
+// written from the host start out just born or never alive.
+pub fn texel(alive: bool) -> [u8; 4] {
+    if alive {
+        [255, 1, 0, 255]
+    } else {
+        [0, 0, 0, 255]
+    }
+}
+
+pub fn cells_to_texture_data(cells: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(cells.len() * 4);
+
+    for &cell in cells {
+        data.extend_from_slice(&texel(cell != 0));
+    }
+
+    data
+}
+
+pub fn texture_data_to_cells(data: &[u8]) -> Vec<u8> {
+    data.chunks_exact(4)
+        .map(|pixel| (pixel[0] > 127) as u8)
+        .collect()
+}
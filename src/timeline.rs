@@ -0,0 +1,222 @@
+use std::collections::VecDeque;
+
+use crate::snapshot::Snapshot;
+
+// Off until `set_timeline_capacity` is called: recording reads the grid back
+// before every step, which would stall the WebGL backend.
+pub const DEFAULT_CAPACITY: usize = 0;
+
+// Ring buffer of recent generations for stepping backwards. Frames behind the
+// current position are kept until the next step, so a scrubber can move
+// forward again without re-running the simulation.
+pub struct Timeline {
+    past: VecDeque<Frame>,
+    future: Vec<Frame>,
+    capacity: usize,
+    // Generations stepped since the timeline was created or cleared, up to
+    // the current position. A step of n generations records one frame, so
+    // this is how callers keep their generation count right while seeking.
+    generation: u64,
+}
+
+struct Frame {
+    snapshot: Snapshot,
+    generation: u64,
+}
+
+impl Timeline {
+    pub fn new(capacity: usize) -> Self {
+        Timeline {
+            past: VecDeque::new(),
+            future: Vec::new(),
+            capacity,
+            generation: 0,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        let excess = self.future.len().saturating_sub(capacity);
+        self.future.drain(..excess);
+        while self.past.len() > capacity {
+            self.past.pop_front();
+        }
+    }
+
+    // Stores the generation a step of `generations` generations is about to
+    // leave. Stepping from a rewound position starts a new branch, so the
+    // frames ahead are dropped.
+    pub fn record(&mut self, before: Snapshot, generations: u64) {
+        self.future.clear();
+        let generation = self.generation;
+        self.generation = generation.saturating_add(generations);
+        if self.capacity == 0 {
+            return;
+        }
+        if self.past.len() == self.capacity {
+            self.past.pop_front();
+        }
+        self.past.push_back(Frame {
+            snapshot: before,
+            generation,
+        });
+    }
+
+    // Moves to `position` (0 is the oldest frame) and returns the frame to
+    // show there, or None when the position does not change.
+    pub fn seek(&mut self, position: usize, current: Snapshot) -> Option<Snapshot> {
+        let position = position.min(self.len());
+        if position == self.position() {
+            return None;
+        }
+
+        let mut current = Frame {
+            snapshot: current,
+            generation: self.generation,
+        };
+        while self.position() > position {
+            let Some(frame) = self.past.pop_back() else {
+                break;
+            };
+            self.future.push(std::mem::replace(&mut current, frame));
+        }
+        while self.position() < position {
+            let Some(frame) = self.future.pop() else {
+                break;
+            };
+            self.past.push_back(std::mem::replace(&mut current, frame));
+        }
+        self.generation = current.generation;
+        Some(current.snapshot)
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    // Number of frames before the current generation.
+    pub fn position(&self) -> usize {
+        self.past.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.past.len() + self.future.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear_future(&mut self) {
+        self.future.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.past.clear();
+        self.future.clear();
+        self.generation = 0;
+    }
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Timeline::new(DEFAULT_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Frames are told apart by their length.
+    fn frame(id: usize) -> Snapshot {
+        Snapshot::from_cells(&vec![0; id])
+    }
+
+    fn seek(timeline: &mut Timeline, position: usize, current: usize) -> Option<usize> {
+        timeline
+            .seek(position, frame(current))
+            .map(|snapshot| snapshot.len())
+    }
+
+    #[test]
+    fn the_ring_keeps_the_newest_frames() {
+        let mut timeline = Timeline::new(3);
+        for id in 1..=5 {
+            timeline.record(frame(id), 1);
+        }
+        assert_eq!((timeline.position(), timeline.len()), (3, 3));
+
+        // The current generation is 6; 1 and 2 have been overwritten.
+        assert_eq!(seek(&mut timeline, 0, 6), Some(3));
+        assert_eq!(seek(&mut timeline, 2, 3), Some(5));
+        assert_eq!(seek(&mut timeline, 3, 5), Some(6));
+
+        timeline.set_capacity(2);
+        assert_eq!(seek(&mut timeline, 0, 6), Some(4));
+        assert!(!Timeline::default().is_enabled());
+    }
+
+    #[test]
+    fn seeks_stay_in_bounds() {
+        let mut timeline = Timeline::new(8);
+        assert_eq!(seek(&mut timeline, 0, 1), None);
+        timeline.record(frame(1), 1);
+        timeline.record(frame(2), 1);
+
+        assert_eq!(seek(&mut timeline, 2, 3), None);
+        assert_eq!(seek(&mut timeline, 100, 3), None);
+        assert_eq!(seek(&mut timeline, 1, 3), Some(2));
+        // Past the end lands on the newest frame, the one seeking left.
+        assert_eq!(seek(&mut timeline, 100, 2), Some(3));
+        assert_eq!(timeline.position(), 2);
+    }
+
+    #[test]
+    fn stepping_or_editing_drops_the_frames_ahead() {
+        let mut timeline = Timeline::new(8);
+        for id in 1..=3 {
+            timeline.record(frame(id), 1);
+        }
+        assert_eq!(seek(&mut timeline, 1, 4), Some(2));
+        assert_eq!((timeline.position(), timeline.len()), (1, 3));
+
+        timeline.clear_future();
+        assert_eq!((timeline.position(), timeline.len()), (1, 1));
+        assert_eq!(seek(&mut timeline, 3, 2), None);
+
+        assert_eq!(seek(&mut timeline, 0, 2), Some(1));
+        timeline.record(frame(7), 1);
+        assert_eq!((timeline.position(), timeline.len()), (1, 1));
+        timeline.clear();
+        assert!(timeline.is_empty());
+    }
+
+    #[test]
+    fn frames_remember_their_generation() {
+        let mut timeline = Timeline::new(2);
+        timeline.record(frame(1), 1);
+        timeline.record(frame(2), 10);
+        timeline.record(frame(3), 100);
+        assert_eq!(timeline.generation(), 111);
+
+        // Frame 1 has been overwritten; frame 2 was left at generation 1.
+        assert_eq!(seek(&mut timeline, 0, 4), Some(2));
+        assert_eq!(timeline.generation(), 1);
+        assert_eq!(seek(&mut timeline, 1, 2), Some(3));
+        assert_eq!(timeline.generation(), 11);
+        assert_eq!(seek(&mut timeline, 2, 3), Some(4));
+        assert_eq!(timeline.generation(), 111);
+
+        timeline.clear();
+        assert_eq!(timeline.generation(), 0);
+    }
+}
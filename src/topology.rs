@@ -0,0 +1,163 @@
+// Bounded grid topologies, named after Golly's rule suffixes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Topology {
+    Plane,
+    #[default]
+    Torus,
+    // Top and bottom edges joined with a twist (":K100*,50").
+    KleinHorizontal,
+    // Left and right edges joined with a twist (":K100,50*").
+    KleinVertical,
+    CrossSurface,
+}
+
+impl Topology {
+    // Parses "T200,100", ":P200,100", "K200*,100", ... Sizes are optional, so a
+    // bare "T" keeps the current grid size.
+    pub fn parse(spec: &str) -> Result<(Topology, Option<(u32, u32)>), String> {
+        let spec = spec.trim();
+        let spec = spec.strip_prefix(':').unwrap_or(spec);
+
+        let mut chars = spec.chars();
+        let kind = chars.next().map(|c| c.to_ascii_uppercase());
+        let sizes = chars.as_str().trim();
+
+        let (width, height, twist_width, twist_height) = if sizes.is_empty() {
+            (None, None, false, false)
+        } else {
+            let (width, height) = sizes
+                .split_once(',')
+                .ok_or_else(|| format!("Invalid topology \"{}\": expected W,H", spec))?;
+            let (width, twist_width) = parse_size(width, spec)?;
+            let (height, twist_height) = parse_size(height, spec)?;
+            (Some(width), Some(height), twist_width, twist_height)
+        };
+
+        let topology = match kind {
+            Some('P') => Topology::Plane,
+            Some('T') => Topology::Torus,
+            // As in Golly, exactly one edge of a Klein bottle is twisted.
+            Some('K') => match (twist_width, twist_height) {
+                (true, false) => Topology::KleinHorizontal,
+                (false, true) => Topology::KleinVertical,
+                _ if sizes.is_empty() => Topology::KleinHorizontal,
+                _ => {
+                    return Err(format!(
+                        "Invalid topology \"{}\": a Klein bottle needs exactly one '*'",
+                        spec
+                    ));
+                }
+            },
+            Some('C') => Topology::CrossSurface,
+            _ => return Err(format!("Unsupported topology \"{}\"", spec)),
+        };
+
+        if (twist_width || twist_height) && !matches!(kind, Some('K')) {
+            return Err(format!(
+                "Invalid topology \"{}\": only Klein bottles take '*'",
+                spec
+            ));
+        }
+
+        Ok((topology, width.zip(height)))
+    }
+
+    pub fn suffix(&self, width: u32, height: u32) -> String {
+        match self {
+            Topology::Plane => format!("P{},{}", width, height),
+            Topology::Torus => format!("T{},{}", width, height),
+            Topology::KleinHorizontal => format!("K{}*,{}", width, height),
+            Topology::KleinVertical => format!("K{},{}*", width, height),
+            Topology::CrossSurface => format!("C{},{}", width, height),
+        }
+    }
+
+    // Whether cells leaving across the x / y edges re-enter on the other side.
+    pub fn wraps(&self) -> (bool, bool) {
+        match self {
+            Topology::Plane => (false, false),
+            _ => (true, true),
+        }
+    }
+
+    // Whether re-entering across the x / y edges mirrors the other coordinate.
+    pub fn twists(&self) -> (bool, bool) {
+        match self {
+            Topology::KleinHorizontal => (false, true),
+            Topology::KleinVertical => (true, false),
+            Topology::CrossSurface => (true, true),
+            _ => (false, false),
+        }
+    }
+
+    // Maps a possibly out-of-range cell onto the grid, or None for dead edges.
+    // Kept in sync with `getCell` in the compute shader.
+    pub fn resolve(&self, x: i64, y: i64, width: u32, height: u32) -> Option<(u32, u32)> {
+        let (wrap_x, wrap_y) = self.wraps();
+        let (twist_x, twist_y) = self.twists();
+        let (width, height) = (width as i64, height as i64);
+        let (mut x, mut y) = (x, y);
+
+        if x < 0 || x >= width {
+            if !wrap_x {
+                return None;
+            }
+            x = x.rem_euclid(width);
+            if twist_x {
+                y = height - 1 - y;
+            }
+        }
+        if y < 0 || y >= height {
+            if !wrap_y {
+                return None;
+            }
+            y = y.rem_euclid(height);
+            if twist_y {
+                x = width - 1 - x;
+            }
+        }
+
+        Some((x as u32, y as u32))
+    }
+}
+
+fn parse_size(size: &str, spec: &str) -> Result<(u32, bool), String> {
+    let size = size.trim();
+    let (size, twisted) = match size.strip_suffix('*') {
+        Some(size) => (size, true),
+        None => (size, false),
+    };
+
+    match size.parse::<u32>() {
+        Ok(0) => Err(format!(
+            "Invalid topology \"{}\": unbounded dimensions are not supported",
+            spec
+        )),
+        Ok(size) => Ok((size, twisted)),
+        Err(_) => Err(format!(
+            "Invalid topology \"{}\": bad size \"{}\"",
+            spec, size
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn klein_bottles_need_exactly_one_twisted_edge() {
+        assert_eq!(
+            Topology::parse("K100*,50"),
+            Ok((Topology::KleinHorizontal, Some((100, 50))))
+        );
+        assert_eq!(
+            Topology::parse(":K100,50*"),
+            Ok((Topology::KleinVertical, Some((100, 50))))
+        );
+        assert_eq!(Topology::parse("K"), Ok((Topology::KleinHorizontal, None)));
+        assert!(Topology::parse("K100,50").is_err());
+        assert!(Topology::parse("K100*,50*").is_err());
+        assert!(Topology::parse("T100*,50").is_err());
+    }
+}
@@ -1,17 +1,46 @@
 use wasm_bindgen::prelude::*;
-use web_sys::{WebGlBuffer, WebGlRenderingContext, WebGlShader, WebGlTexture};
-
-pub fn init_webgl_context(canvas_id: &str) -> Result<WebGlRenderingContext, JsValue> {
-    let document = web_sys::window().unwrap().document().unwrap();
-    let canvas = document.get_element_by_id(canvas_id).unwrap();
-    let canvas: web_sys::HtmlCanvasElement = canvas.dyn_into::<web_sys::HtmlCanvasElement>()?;
-    let gl: WebGlRenderingContext = canvas
-        .get_context("webgl")?
-        .unwrap()
-        .dyn_into::<WebGlRenderingContext>()
-        .unwrap();
-
-    gl.viewport(0, 0, canvas.width() as i32, canvas.height() as i32);
+use web_sys::{
+    HtmlCanvasElement, OffscreenCanvas, WebGlBuffer, WebGlRenderingContext, WebGlShader,
+    WebGlTexture,
+};
+
+#[derive(Clone)]
+pub enum RenderTarget {
+    Canvas(HtmlCanvasElement),
+    Offscreen(OffscreenCanvas),
+}
+
+impl RenderTarget {
+    pub fn from_id(canvas_id: &str) -> Result<RenderTarget, JsValue> {
+        let document = web_sys::window()
+            .and_then(|window| window.document())
+            .ok_or_else(|| JsValue::from_str("No document available"))?;
+        let canvas = document
+            .get_element_by_id(canvas_id)
+            .ok_or_else(|| JsValue::from_str(&format!("No element with id \"{}\"", canvas_id)))?;
+        let canvas = canvas.dyn_into::<HtmlCanvasElement>()?;
+        Ok(RenderTarget::Canvas(canvas))
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        match self {
+            RenderTarget::Canvas(canvas) => (canvas.width(), canvas.height()),
+            RenderTarget::Offscreen(canvas) => (canvas.width(), canvas.height()),
+        }
+    }
+}
+
+pub fn init_webgl_context(target: &RenderTarget) -> Result<WebGlRenderingContext, JsValue> {
+    let context = match target {
+        RenderTarget::Canvas(canvas) => canvas.get_context("webgl")?,
+        RenderTarget::Offscreen(canvas) => canvas.get_context("webgl")?,
+    };
+    let gl = context
+        .ok_or_else(|| JsValue::from_str("WebGL is not supported"))?
+        .dyn_into::<WebGlRenderingContext>()?;
+
+    let (width, height) = target.size();
+    gl.viewport(0, 0, width as i32, height as i32);
     gl.get_extension("OES_texture_float").ok();
 
     Ok(gl)
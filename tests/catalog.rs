@@ -0,0 +1,260 @@
+use game_of_life::catalog::{Behaviour, CATALOG, CatalogEntry, Growth};
+use game_of_life::hashlife::Universe;
+use game_of_life::registry::Registry;
+use game_of_life::rle;
+
+fn universe(entry: &CatalogEntry) -> Universe {
+    let (pattern, _) = rle::parse(entry.rle).unwrap_or_else(|e| panic!("{}: {}", entry.name, e));
+    let mut universe = Universe::new();
+    universe.add_pattern(&pattern, 0, 0).unwrap();
+    universe
+}
+
+fn sorted_cells(universe: &Universe) -> Vec<(i64, i64)> {
+    let mut cells = universe.cells();
+    cells.sort_unstable();
+    cells
+}
+
+// The live cells moved so their bounding box starts at (0, 0).
+fn shape(universe: &Universe) -> Vec<(i64, i64)> {
+    let Some((min_x, min_y, _, _)) = universe.bounds() else {
+        return Vec::new();
+    };
+    let mut cells: Vec<_> = universe
+        .cells()
+        .into_iter()
+        .map(|(x, y)| (x - min_x, y - min_y))
+        .collect();
+    cells.sort_unstable();
+    cells
+}
+
+fn check_periodic(entry: &CatalogEntry, period: u32, dx: i32, dy: i32) {
+    let mut universe = universe(entry);
+    let start = sorted_cells(&universe);
+    let start_shape = shape(&universe);
+
+    for generation in 1..period {
+        universe.step(1).unwrap();
+        assert_ne!(
+            shape(&universe),
+            start_shape,
+            "{} repeats after {} generations, before its period of {}",
+            entry.name,
+            generation,
+            period
+        );
+    }
+    universe.step(1).unwrap();
+
+    let expected: Vec<_> = start
+        .iter()
+        .map(|&(x, y)| (x + dx as i64, y + dy as i64))
+        .collect();
+    assert_eq!(
+        sorted_cells(&universe),
+        expected,
+        "{} does not return moved by ({}, {}) after {} generations",
+        entry.name,
+        dx,
+        dy,
+        period
+    );
+}
+
+// Once the exhaust has settled, the cells around the engine repeat every
+// period (shifted by the displacement) and no smaller period fits, and the
+// population grows as claimed.
+fn check_growing(entry: &CatalogEntry, period: u32, dx: i32, dy: i32, growth: Growth) {
+    const MARGIN: i64 = 8;
+    // Long enough for the slowest exhaust, the Puffer train's, to settle.
+    const SETTLED: u64 = 10_000;
+
+    let mut universe = universe(entry);
+    let (min_x, min_y, max_x, max_y) = universe.bounds().unwrap();
+    let width = (max_x - min_x + 1 + 2 * MARGIN) as u32;
+    let height = (max_y - min_y + 1 + 2 * MARGIN) as u32;
+    // The window around the start, moved along with the engine.
+    let window = |universe: &Universe| {
+        let generations = universe.generation() as i64;
+        let shift = |delta: i32| delta as i64 * generations / period as i64;
+        universe.read_region(
+            min_x - MARGIN + shift(dx),
+            min_y - MARGIN + shift(dy),
+            width,
+            height,
+        )
+    };
+
+    universe
+        .step(SETTLED.div_ceil(period as u64) * period as u64)
+        .unwrap();
+    let mut windows = vec![window(&universe)];
+    assert!(
+        windows[0].iter().any(|&cell| cell != 0),
+        "{} left its window",
+        entry.name
+    );
+    for _ in 0..3 * period {
+        universe.step(1).unwrap();
+        windows.push(window(&universe));
+    }
+
+    let period = period as usize;
+    for generation in period..windows.len() {
+        assert_eq!(
+            windows[generation],
+            windows[generation - period],
+            "{} does not repeat moved by ({}, {}) every {} generations",
+            entry.name,
+            dx,
+            dy,
+            period
+        );
+    }
+    // A smaller period would divide this one, with the displacement scaled
+    // down to whole cells.
+    let smaller = (1..period).filter(|&d| {
+        period.is_multiple_of(d)
+            && (dx * d as i32) % period as i32 == 0
+            && (dy * d as i32) % period as i32 == 0
+    });
+    for divisor in smaller {
+        assert!(
+            (divisor..windows.len())
+                .any(|generation| windows[generation] != windows[generation - divisor]),
+            "{} also repeats every {} generations, less than its period of {}",
+            entry.name,
+            divisor,
+            period
+        );
+    }
+
+    let every = match growth {
+        Growth::Linear { every, .. } | Growth::Quadratic { every } => every as u64,
+    };
+    let mut populations = vec![universe.population()];
+    for _ in 0..4 {
+        universe.step(every).unwrap();
+        populations.push(universe.population());
+    }
+    let increments: Vec<i64> = populations
+        .windows(2)
+        .map(|pair| pair[1] as i64 - pair[0] as i64)
+        .collect();
+    match growth {
+        Growth::Linear { cells, .. } => assert!(
+            increments
+                .iter()
+                .all(|&increment| increment == cells as i64),
+            "{} does not gain {} cells every {} generations: {:?}",
+            entry.name,
+            cells,
+            every,
+            increments
+        ),
+        Growth::Quadratic { .. } => assert!(
+            increments.windows(2).all(|pair| pair[1] > pair[0]),
+            "{} does not gain more cells every {} generations: {:?}",
+            entry.name,
+            every,
+            increments
+        ),
+    }
+}
+
+fn check_methuselah(entry: &CatalogEntry, lifespan: u32, population: u64) {
+    let mut universe = universe(entry);
+    universe.step(lifespan as u64).unwrap();
+    assert_eq!(
+        universe.population(),
+        population,
+        "{} population at generation {}",
+        entry.name,
+        lifespan
+    );
+
+    // What is left (still lifes, blinkers and escaping gliders) keeps the same
+    // population from then on.
+    for _ in 0..12 {
+        universe.step(1).unwrap();
+        assert_eq!(
+            universe.population(),
+            population,
+            "{} is still changing after generation {}",
+            entry.name,
+            lifespan
+        );
+    }
+}
+
+#[test]
+fn catalog_patterns_parse() {
+    for entry in CATALOG {
+        let (pattern, _) =
+            rle::parse(entry.rle).unwrap_or_else(|e| panic!("{}: {}", entry.name, e));
+        assert!(pattern.population() > 0, "{} is empty", entry.name);
+    }
+}
+
+#[test]
+fn catalog_names_are_unique() {
+    for (index, entry) in CATALOG.iter().enumerate() {
+        assert!(
+            !CATALOG[..index]
+                .iter()
+                .any(|other| other.name.eq_ignore_ascii_case(entry.name)),
+            "{} is listed twice",
+            entry.name
+        );
+    }
+}
+
+#[test]
+fn catalog_is_registered() {
+    let registry = Registry::builtin();
+    for entry in CATALOG {
+        let registered = registry
+            .get(entry.name)
+            .unwrap_or_else(|| panic!("{} is not registered", entry.name));
+        assert_eq!(registered.category, entry.category);
+    }
+}
+
+#[test]
+fn periodic_patterns_match_period_and_displacement() {
+    for entry in CATALOG {
+        if let Behaviour::Periodic { period, dx, dy } = entry.behaviour {
+            check_periodic(entry, period, dx, dy);
+        }
+    }
+}
+
+#[test]
+fn growing_patterns_match_period_and_displacement() {
+    for entry in CATALOG {
+        if let Behaviour::Growing {
+            period,
+            dx,
+            dy,
+            growth,
+        } = entry.behaviour
+        {
+            check_growing(entry, period, dx, dy, growth);
+        }
+    }
+}
+
+#[test]
+fn methuselahs_settle_on_time() {
+    for entry in CATALOG {
+        if let Behaviour::Methuselah {
+            lifespan,
+            population,
+        } = entry.behaviour
+        {
+            check_methuselah(entry, lifespan, population as u64);
+        }
+    }
+}
@@ -1,21 +1,30 @@
-import init, { GameOfLife } from "./pkg/game_of_life.js";
+import init, { ColorMode, GameOfLife, Pattern, PlaceMode, Symmetry, Theme, Transform } from "./pkg/game_of_life.js";
 
 const GRID_MIN_SIZE = 10;
 const GRID_MAX_SIZE = 2000;
 const BRUSH_CURSOR_SVG = "url('data:image/svg+xml;utf8,<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"20\" height=\"20\"><circle cx=\"10\" cy=\"10\" r=\"8\" fill=\"none\" stroke=\"%2300ff41\" stroke-width=\"2\"/></svg>') 10 10, crosshair";
 const CANVAS_ID = "gameCanvas";
+const ZOOM_STEP = 1.2;
+// Generations kept for STEP BACK while "Record timeline" is ticked.
+const TIMELINE_CAPACITY = 32;
+// In the order of `set_colors` and `colors()`.
+const PALETTE_INPUTS = ["colorAlive", "colorDead", "colorGrid", "colorBackground"];
 let gameOfLife = null;
 let isPlaying = false;
 let animationId = null;
 let speed = 100;
 let generation = 0;
+let generationsPerFrame = 1;
 let fpsCounter = 0;
 let lastFpsTime = Date.now();
 
 let drawMode = "single";
 let isDrawing = false;
 let lastDrawPos = null;
+let isPanning = false;
+let lastPanPos = null;
 let brushSize = 3;
+let eraseMode = false;
 
 let previewCanvas = null;
 let previewCtx = null;
@@ -42,6 +51,8 @@ async function run() {
         lastValidGridHeight = gridHeight;
         
         setupEventListeners();
+        buildPatternPalette();
+        syncGridSettings();
         gameOfLife.render();
         updateStats();
         createPreviewCanvas();
@@ -61,6 +72,8 @@ function setupEventListeners() {
     canvas.addEventListener("mousemove", handleCanvasMouseMove);
     canvas.addEventListener("mouseup", handleCanvasMouseUp);
     canvas.addEventListener("mouseleave", handleCanvasMouseUp);
+    canvas.addEventListener("wheel", handleCanvasWheel, { passive: false });
+    canvas.addEventListener("contextmenu", (e) => e.preventDefault());
     
     canvas.addEventListener("touchstart", handleCanvasTouchStart, { passive: false });
     canvas.addEventListener("touchmove", handleCanvasTouchMove, { passive: false });
@@ -68,8 +81,37 @@ function setupEventListeners() {
     
     document.getElementById("playPause").addEventListener("click", togglePlayPause);
     document.getElementById("step").addEventListener("click", step);
+    document.getElementById("stepBack").addEventListener("click", stepBack);
+    document.getElementById("recordTimeline").addEventListener("change", (e) => {
+        if (!gameOfLife) return;
+        gameOfLife.set_timeline_capacity(e.target.checked ? TIMELINE_CAPACITY : 0);
+        updateTimeline();
+    });
+    document.getElementById("timeline").addEventListener("input", (e) => {
+        seekTimeline(parseInt(e.target.value));
+    });
     document.getElementById("randomize").addEventListener("click", randomize);
+    document.getElementById("soupFromSeed").addEventListener("click", soupFromSeed);
+    document.getElementById("soupDensity").addEventListener("input", (e) => {
+        document.getElementById("soupDensityValue").textContent = e.target.value;
+    });
     document.getElementById("clear").addEventListener("click", clear);
+    document.getElementById("resetView").addEventListener("click", resetView);
+    document.getElementById("undo").addEventListener("click", undo);
+    document.getElementById("redo").addEventListener("click", redo);
+    
+    document.addEventListener("keydown", (e) => {
+        if (!(e.ctrlKey || e.metaKey) || e.target.matches("input, textarea")) return;
+        
+        const key = e.key.toLowerCase();
+        if (key === "z" && !e.shiftKey) {
+            e.preventDefault();
+            undo();
+        } else if (key === "y" || (key === "z" && e.shiftKey)) {
+            e.preventDefault();
+            redo();
+        }
+    });
     
     document.querySelectorAll(".tool-btn").forEach(btn => {
         btn.addEventListener("click", (e) => {
@@ -77,6 +119,8 @@ function setupEventListeners() {
         });
     });
     
+    document.getElementById("eraseToggle").addEventListener("click", toggleEraseMode);
+    
     document.getElementById("applyGridSize").addEventListener("click", resizeGrid);
     
     document.getElementById("gridWidth").addEventListener("keypress", (e) => {
@@ -93,13 +137,21 @@ function setupEventListeners() {
         }
     });
     
-    document.getElementById("glider").addEventListener("click", loadGlider);
-    document.getElementById("blinker").addEventListener("click", loadBlinker);
-    document.getElementById("beacon").addEventListener("click", loadBeacon);
-    document.getElementById("toad").addEventListener("click", loadToad);
-    document.getElementById("spaceship").addEventListener("click", loadSpaceship);
-    document.getElementById("pulsar").addEventListener("click", loadPulsar);
-    document.getElementById("gliderGun").addEventListener("click", loadGliderGun);
+    document.getElementById("applyRule").addEventListener("click", applyRule);
+    document.getElementById("topology").addEventListener("change", applyTopology);
+    document.getElementById("ruleInput").addEventListener("keypress", (e) => {
+        if (e.key === "Enter") {
+            e.preventDefault();
+            applyRule();
+        }
+    });
+    
+    document.getElementById("importPattern").addEventListener("click", importPattern);
+    document.getElementById("exportPattern").addEventListener("click", exportPattern);
+    document.getElementById("placePattern").addEventListener("click", placePattern);
+    document.getElementById("registerPattern").addEventListener("click", registerPattern);
+    document.getElementById("runCensus").addEventListener("click", runCensus);
+    document.getElementById("exportCensus").addEventListener("click", exportCensus);
     
     const speedSlider = document.getElementById("speed");
     const brushSlider = document.getElementById("brushSize");
@@ -109,6 +161,31 @@ function setupEventListeners() {
         document.getElementById("speedValue").textContent = speed;
     });
     
+    document.getElementById("detectPeriods").addEventListener("change", (e) => {
+        if (!gameOfLife) return;
+        try {
+            gameOfLife.set_period_detection(e.target.checked);
+            updateStats();
+        } catch (error) {
+            console.error("Error toggling period detection:", error);
+        }
+    });
+    
+    document.getElementById("theme").addEventListener("change", applyTheme);
+    document.getElementById("colorMode").addEventListener("change", applyColors);
+    for (const id of [
+        "colorAlive", "colorDead", "colorGrid", "colorBackground", "gridLines",
+        "ageYoung", "ageMiddle", "ageOld", "ageMax", "trailLength", "trailColor",
+    ]) {
+        document.getElementById(id).addEventListener("change", applyColors);
+    }
+    
+    document.getElementById("generationsPerFrame").addEventListener("change", (e) => {
+        const value = parseInt(e.target.value);
+        generationsPerFrame = isNaN(value) ? 1 : Math.max(1, Math.min(10000, value));
+        e.target.value = generationsPerFrame;
+    });
+    
     brushSlider.addEventListener("input", (e) => {
         brushSize = parseInt(e.target.value);
         document.getElementById("brushSizeValue").textContent = brushSize;
@@ -123,6 +200,37 @@ function setupEventListeners() {
     });
 }
 
+// Loads a built-in theme and shows its colours in the palette inputs.
+function applyTheme() {
+    if (!gameOfLife) return;
+    
+    gameOfLife.set_theme(Theme[document.getElementById("theme").value]);
+    const colors = gameOfLife.colors();
+    PALETTE_INPUTS.forEach((id, index) => {
+        document.getElementById(id).value = colors[index];
+    });
+    gameOfLife.render();
+}
+
+function applyColors() {
+    if (!gameOfLife) return;
+    
+    const colors = ["ageYoung", "ageMiddle", "ageOld"].map((id) => document.getElementById(id).value);
+    const maxAge = parseInt(document.getElementById("ageMax").value);
+    const trailLength = parseInt(document.getElementById("trailLength").value);
+    const palette = PALETTE_INPUTS.map((id) => document.getElementById(id).value);
+    try {
+        gameOfLife.set_colors(...palette);
+        gameOfLife.set_grid_lines(document.getElementById("gridLines").checked);
+        gameOfLife.set_color_mode(ColorMode[document.getElementById("colorMode").value]);
+        gameOfLife.set_age_gradient(colors, isNaN(maxAge) ? 100 : maxAge);
+        gameOfLife.set_trail(isNaN(trailLength) ? 0 : trailLength, document.getElementById("trailColor").value);
+        gameOfLife.render();
+    } catch (error) {
+        console.error("Error applying colours:", error);
+    }
+}
+
 function createPreviewCanvas() {
     const canvas = document.getElementById(CANVAS_ID);
     const container = canvas.parentElement;
@@ -148,17 +256,8 @@ function drawLinePreview(startX, startY, endX, endY) {
     
     previewCtx.clearRect(0, 0, previewCanvas.width, previewCanvas.height);
     
-    const canvas = document.getElementById(CANVAS_ID);
-    const gridWidth = parseInt(document.getElementById("gridWidth").value);
-    const gridHeight = parseInt(document.getElementById("gridHeight").value);
-    
-    const cellWidth = canvas.width / gridWidth;
-    const cellHeight = canvas.height / gridHeight;
-    
-    const canvasStartX = (startX + 0.5) * cellWidth;
-    const canvasStartY = (gridHeight - startY - 0.5) * cellHeight;
-    const canvasEndX = (endX + 0.5) * cellWidth;
-    const canvasEndY = (gridHeight - endY - 0.5) * cellHeight;
+    const [canvasStartX, canvasStartY] = gameOfLife.cell_to_screen(startX, startY);
+    const [canvasEndX, canvasEndY] = gameOfLife.cell_to_screen(endX, endY);
     
     previewCtx.strokeStyle = '#00ff41';
     previewCtx.lineWidth = 2;
@@ -176,31 +275,45 @@ function clearLinePreview() {
     isPreviewActive = false;
 }
 
-function getCanvasCoordinates(canvas, clientX, clientY) {
+function getCanvasPixel(canvas, clientX, clientY) {
     const rect = canvas.getBoundingClientRect();
     const scaleX = canvas.width / rect.width;
     const scaleY = canvas.height / rect.height;
     
-    const x = Math.floor((clientX - rect.left) * scaleX);
-    const y = Math.floor((clientY - rect.top) * scaleY);
-    
-    const gridWidth = parseInt(document.getElementById("gridWidth").value);
-    const gridHeight = parseInt(document.getElementById("gridHeight").value);
+    return {
+        x: (clientX - rect.left) * scaleX,
+        y: (clientY - rect.top) * scaleY,
+    };
+}
+
+// Returns null when the pointer is outside the grid.
+function getCanvasCoordinates(canvas, clientX, clientY) {
+    const { x, y } = getCanvasPixel(canvas, clientX, clientY);
+    const cell = gameOfLife.screen_to_cell(x, y);
     
-    const cellX = Math.floor((x / canvas.width) * gridWidth);
-    const cellY = gridHeight - 1 - Math.floor((y / canvas.height) * gridHeight);
+    if (!cell) return null;
     
-    return { cellX, cellY };
+    return { cellX: cell[0], cellY: cell[1] };
 }
 
 function handleCanvasMouseDown(e) {
     if (!gameOfLife) return;
     
     const canvas = document.getElementById(CANVAS_ID);
-    const { cellX, cellY } = getCanvasCoordinates(canvas, e.clientX, e.clientY);
+    
+    if (e.button === 1 || e.button === 2) {
+        isPanning = true;
+        lastPanPos = getCanvasPixel(canvas, e.clientX, e.clientY);
+        return;
+    }
+    
+    const coords = getCanvasCoordinates(canvas, e.clientX, e.clientY);
+    if (!coords) return;
+    const { cellX, cellY } = coords;
     
     isDrawing = true;
     lastDrawPos = { x: cellX, y: cellY };
+    gameOfLife.begin_edit_group();
     
     if (drawMode === "single") {
         gameOfLife.toggle_cell(cellX, cellY);
@@ -215,7 +328,18 @@ function handleCanvasMouseMove(e) {
     if (!gameOfLife) return;
     
     const canvas = document.getElementById(CANVAS_ID);
-    const { cellX, cellY } = getCanvasCoordinates(canvas, e.clientX, e.clientY);
+    
+    if (isPanning) {
+        const pos = getCanvasPixel(canvas, e.clientX, e.clientY);
+        gameOfLife.pan(pos.x - lastPanPos.x, pos.y - lastPanPos.y);
+        gameOfLife.render();
+        lastPanPos = pos;
+        return;
+    }
+    
+    const coords = getCanvasCoordinates(canvas, e.clientX, e.clientY);
+    if (!coords) return;
+    const { cellX, cellY } = coords;
     
     if (drawMode === "line" && isDrawing && lastDrawPos) {
         drawLinePreview(lastDrawPos.x, lastDrawPos.y, cellX, cellY);
@@ -228,21 +352,48 @@ function handleCanvasMouseMove(e) {
 }
 
 function handleCanvasMouseUp(e) {
+    if (isPanning) {
+        isPanning = false;
+        lastPanPos = null;
+        return;
+    }
+    
     if (!gameOfLife || !isDrawing) return;
     
     const canvas = document.getElementById(CANVAS_ID);
-    const { cellX, cellY } = getCanvasCoordinates(canvas, e.clientX, e.clientY);
+    const coords = getCanvasCoordinates(canvas, e.clientX, e.clientY);
     
     if (drawMode === "line" && lastDrawPos) {
         clearLinePreview();
-        gameOfLife.draw_line(lastDrawPos.x, lastDrawPos.y, cellX, cellY);
-        gameOfLife.render();
+        if (coords) {
+            gameOfLife.draw_line(lastDrawPos.x, lastDrawPos.y, coords.cellX, coords.cellY, drawValue());
+            gameOfLife.render();
+        }
     }
     
+    gameOfLife.end_edit_group();
     isDrawing = false;
     lastDrawPos = null;
 }
 
+function handleCanvasWheel(e) {
+    if (!gameOfLife) return;
+    e.preventDefault();
+    
+    const canvas = document.getElementById(CANVAS_ID);
+    const { x, y } = getCanvasPixel(canvas, e.clientX, e.clientY);
+    
+    gameOfLife.zoom_at(x, y, e.deltaY < 0 ? ZOOM_STEP : 1 / ZOOM_STEP);
+    gameOfLife.render();
+}
+
+function resetView() {
+    if (!gameOfLife) return;
+    
+    gameOfLife.reset_view();
+    gameOfLife.render();
+}
+
 function handleCanvasTouchStart(e) {
     e.preventDefault();
     const touch = e.touches[0];
@@ -266,22 +417,30 @@ function handleCanvasTouchEnd(e) {
 function drawBrush(centerX, centerY) {
     const radius = Math.floor(brushSize / 2);
     
-    for (let dx = -radius; dx <= radius; dx++) {
-        for (let dy = -radius; dy <= radius; dy++) {
-            if (dx * dx + dy * dy <= radius * radius) {
-                const x = centerX + dx;
-                const y = centerY + dy;
-                if (x >= 0 && y >= 0) {
-                    gameOfLife.set_cell(x, y, 255);
-                }
-            }
-        }
+    if (centerX >= 0 && centerY >= 0) {
+        gameOfLife.add_cells_in_area(centerX, centerY, radius, drawValue());
     }
 }
 
+function drawValue() {
+    return eraseMode ? 0 : 255;
+}
+
+function toggleEraseMode() {
+    eraseMode = !eraseMode;
+    
+    const button = document.getElementById("eraseToggle");
+    button.textContent = eraseMode ? "ERASE: ON" : "ERASE: OFF";
+    button.classList.toggle("active", eraseMode);
+    
+    document.getElementById("currentDrawMode").textContent =
+        drawMode.toUpperCase() + (eraseMode ? " (ERASE)" : "");
+}
+
 function setDrawMode(mode) {
     drawMode = mode;
-    document.getElementById("currentDrawMode").textContent = mode.toUpperCase();
+    document.getElementById("currentDrawMode").textContent =
+        mode.toUpperCase() + (eraseMode ? " (ERASE)" : "");
     
     document.querySelectorAll(".tool-btn").forEach(btn => {
         btn.classList.remove("active");
@@ -300,9 +459,9 @@ function gameLoop() {
     if (!gameOfLife || !isPlaying) return;
     
     try {
-        gameOfLife.step();
+        gameOfLife.step_n(generationsPerFrame);
         gameOfLife.render();
-        generation++;
+        generation += generationsPerFrame;
         updateStats();
         
         fpsCounter++;
@@ -326,6 +485,48 @@ function gameLoop() {
 
 function updateStats() {
     document.getElementById("generationCounter").textContent = generation;
+    
+    if (!gameOfLife) return;
+    
+    const stats = gameOfLife.statistics();
+    document.getElementById("populationCounter").textContent = stats.population;
+    document.getElementById("birthsDeathsCounter").textContent = `${stats.births}/${stats.deaths}`;
+    document.getElementById("densityCounter").textContent = `${(stats.density * 100).toFixed(2)}%`;
+    document.getElementById("boundsCounter").textContent = stats.population > 0
+        ? `(${stats.min_x},${stats.min_y})-(${stats.max_x},${stats.max_y})`
+        : "--";
+    stats.free();
+    
+    updateTimeline();
+    updatePeriod();
+}
+
+function updatePeriod() {
+    const counter = document.getElementById("periodCounter");
+    if (!gameOfLife.period_detection()) {
+        counter.textContent = "--";
+        return;
+    }
+    
+    const periodicity = gameOfLife.periodicity();
+    if (!periodicity) {
+        counter.textContent = `watching ${gameOfLife.period_generations()} gens`;
+        return;
+    }
+    
+    const speed = periodicity.speed ? `, ${periodicity.speed}` : "";
+    counter.textContent = `stable after ${periodicity.stable_after} gens, period ${periodicity.period}${speed}`;
+    periodicity.free();
+}
+
+function updateTimeline() {
+    const position = gameOfLife.timeline_position();
+    const length = gameOfLife.timeline_length();
+    const slider = document.getElementById("timeline");
+    
+    slider.max = length;
+    slider.value = position;
+    document.getElementById("timelineValue").textContent = `${position}/${length}`;
 }
 
 function togglePlayPause() {
@@ -369,11 +570,60 @@ function step() {
     }
 }
 
+function stepBack() {
+    if (!gameOfLife) return;
+    
+    seekTimeline(gameOfLife.timeline_position() - 1);
+}
+
+// Generations before the current one are kept in a ring buffer, so the
+// scrubber can rewind and fast-forward without re-running the simulation.
+function seekTimeline(position) {
+    if (!gameOfLife || position < 0) return;
+    
+    stop();
+    
+    try {
+        // Frames recorded with several generations per frame span the whole
+        // batch, so the engine reports how far the grid actually moved.
+        const moved = gameOfLife.seek_timeline(position);
+        if (moved !== 0) {
+            generation += moved;
+            gameOfLife.render();
+        }
+        updateStats();
+    } catch (error) {
+        console.error("Error seeking timeline:", error);
+    }
+}
+
+// Picks a fresh seed and shows it, so the soup can be shared and replayed.
 function randomize() {
+    document.getElementById("soupSeed").value = Math.floor(Math.random() * 4294967296);
+    soupFromSeed();
+}
+
+// A symmetric soup needs a square region, so C4 and D8 use the largest
+// square centred on the grid.
+function soupFromSeed() {
     if (!gameOfLife) return;
     
     try {
-        gameOfLife.randomize();
+        const seed = (parseInt(document.getElementById("soupSeed").value) || 0) >>> 0;
+        const density = parseInt(document.getElementById("soupDensity").value) / 100;
+        const symmetry = Symmetry[document.getElementById("soupSymmetry").value];
+        
+        const gridWidth = gameOfLife.width();
+        const gridHeight = gameOfLife.height();
+        let width = gridWidth;
+        let height = gridHeight;
+        if (symmetry === Symmetry.C4 || symmetry === Symmetry.D8) {
+            width = height = Math.min(gridWidth, gridHeight);
+        }
+        const x = Math.floor((gridWidth - width) / 2);
+        const y = Math.floor((gridHeight - height) / 2);
+        
+        gameOfLife.randomize_with(seed, density, x, y, width, height, symmetry);
         gameOfLife.render();
         generation = 0;
         updateStats();
@@ -395,6 +645,32 @@ function clear() {
     }
 }
 
+function undo() {
+    if (!gameOfLife) return;
+    
+    try {
+        if (gameOfLife.undo()) {
+            gameOfLife.render();
+            updateStats();
+        }
+    } catch (error) {
+        console.error("Error in undo:", error);
+    }
+}
+
+function redo() {
+    if (!gameOfLife) return;
+    
+    try {
+        if (gameOfLife.redo()) {
+            gameOfLife.render();
+            updateStats();
+        }
+    } catch (error) {
+        console.error("Error in redo:", error);
+    }
+}
+
 function resizeGrid() {
     const gridWidthInput = document.getElementById("gridWidth");
     const gridHeightInput = document.getElementById("gridHeight");
@@ -444,6 +720,7 @@ function resizeGrid() {
         lastValidGridHeight = gridHeight;
         
         gameOfLife.resize(gridWidth, gridHeight);
+        syncGridSettings();
         gameOfLife.render();
         generation = 0;
         updateStats();
@@ -460,87 +737,241 @@ function resizeGrid() {
     }
 }
 
-function loadGlider() {
+function applyRule() {
+    const ruleInput = document.getElementById("ruleInput");
+    ruleInput.blur();
+    
     if (!gameOfLife) return;
+    
     try {
-        gameOfLife.load_glider();
+        gameOfLife.set_rule(ruleInput.value);
+        syncGridSettings();
         gameOfLife.render();
-        generation = 0;
-        updateStats();
+        console.log(`Rule set to ${gameOfLife.rule()}`);
     } catch (error) {
-        console.error("Error loading glider:", error);
+        console.error("Error setting rule:", error);
+        ruleInput.value = gameOfLife.rule();
     }
 }
 
-function loadBlinker() {
+function applyTopology() {
     if (!gameOfLife) return;
+    
     try {
-        gameOfLife.load_oscillator();
-        gameOfLife.render();
+        gameOfLife.set_topology(document.getElementById("topology").value);
+        syncGridSettings();
+    } catch (error) {
+        console.error("Error setting topology:", error);
+    }
+}
+
+// Rules and imported patterns may change the grid size and topology.
+function syncGridSettings() {
+    const gridWidth = gameOfLife.width();
+    const gridHeight = gameOfLife.height();
+    
+    if (gridWidth !== lastValidGridWidth || gridHeight !== lastValidGridHeight) {
         generation = 0;
         updateStats();
-    } catch (error) {
-        console.error("Error loading blinker:", error);
     }
+    
+    document.getElementById("gridWidth").value = gridWidth;
+    document.getElementById("gridHeight").value = gridHeight;
+    lastValidGridWidth = gridWidth;
+    lastValidGridHeight = gridHeight;
+    
+    const rule = gameOfLife.rule();
+    document.getElementById("ruleInput").value = rule;
+    document.getElementById("topology").value = rule.split(":")[1].charAt(0);
 }
 
-function loadBeacon() {
+function importPattern() {
     if (!gameOfLife) return;
+    
+    const text = document.getElementById("patternText").value;
+    const gridWidth = parseInt(document.getElementById("gridWidth").value);
+    const gridHeight = parseInt(document.getElementById("gridHeight").value);
+    
+    const format = document.getElementById("patternFormat").value;
+    const x = Math.floor(gridWidth / 4);
+    const y = Math.floor(gridHeight / 4);
+    
     try {
-        gameOfLife.load_beacon();
+        if (format === "plaintext") {
+            gameOfLife.load_plaintext(text, x, y);
+        } else if (format === "life106") {
+            gameOfLife.load_life106(text, x, y);
+        } else if (format === "macrocell") {
+            gameOfLife.load_macrocell(text, x, y);
+        } else if (format === "apgcode") {
+            gameOfLife.load_apgcode(text, x, y);
+        } else {
+            gameOfLife.load_rle(text, x, y);
+        }
+        syncGridSettings();
         gameOfLife.render();
         generation = 0;
         updateStats();
     } catch (error) {
-        console.error("Error loading beacon:", error);
+        console.error("Error importing pattern:", error);
     }
 }
 
-function loadToad() {
+// Stamps the RLE or apgcode in the text box onto the current grid instead of
+// replacing it.
+function placePattern() {
     if (!gameOfLife) return;
+    
+    const text = document.getElementById("patternText").value;
+    const transform = Transform[document.getElementById("placeTransform").value];
+    const mode = PlaceMode[document.getElementById("placeMode").value];
+    const x = Math.floor(gameOfLife.width() / 4);
+    const y = Math.floor(gameOfLife.height() / 4);
+    
     try {
-        gameOfLife.load_toad();
+        const format = document.getElementById("patternFormat").value;
+        const pattern = format === "apgcode" ? Pattern.from_apgcode(text) : Pattern.from_rle(text);
+        gameOfLife.place_pattern(pattern, x, y, transform, mode);
+        pattern.free();
         gameOfLife.render();
-        generation = 0;
         updateStats();
     } catch (error) {
-        console.error("Error loading toad:", error);
+        console.error("Error placing pattern:", error);
     }
 }
 
-function loadSpaceship() {
+function exportPattern() {
     if (!gameOfLife) return;
+    
+    const format = document.getElementById("patternFormat").value;
+    
     try {
-        gameOfLife.load_spaceship();
-        gameOfLife.render();
-        generation = 0;
-        updateStats();
+        let text;
+        if (format === "plaintext") {
+            text = gameOfLife.export_plaintext();
+        } else if (format === "life106") {
+            text = gameOfLife.export_life106();
+        } else if (format === "macrocell") {
+            text = gameOfLife.export_macrocell();
+        } else if (format === "apgcode") {
+            text = gameOfLife.export_apgcode();
+        } else {
+            text = gameOfLife.export_rle();
+        }
+        document.getElementById("patternText").value = text;
     } catch (error) {
-        console.error("Error loading spaceship:", error);
+        console.error("Error exporting pattern:", error);
     }
 }
 
-function loadPulsar() {
+// Lists the objects on the grid, most common first.
+function runCensus() {
     if (!gameOfLife) return;
+    
+    const table = document.getElementById("censusTable");
+    table.replaceChildren();
     try {
-        gameOfLife.load_pulsar();
-        gameOfLife.render();
-        generation = 0;
-        updateStats();
+        const header = table.insertRow();
+        for (const label of ["apgcode", "kind", "period", "count"]) {
+            const cell = document.createElement("th");
+            cell.textContent = label;
+            header.appendChild(cell);
+        }
+        
+        const kinds = ["still life", "oscillator", "spaceship", "unstable"];
+        for (const entry of gameOfLife.census()) {
+            const row = table.insertRow();
+            const code = row.insertCell();
+            if (entry.apgcode) {
+                // Clicking a code copies it to the import box, ready to place.
+                const link = document.createElement("a");
+                link.href = "#";
+                link.textContent = entry.apgcode;
+                link.addEventListener("click", (e) => {
+                    e.preventDefault();
+                    document.getElementById("patternFormat").value = "apgcode";
+                    document.getElementById("patternText").value = link.textContent;
+                });
+                code.appendChild(link);
+            } else {
+                code.textContent = "(unstable)";
+            }
+            row.insertCell().textContent = kinds[entry.kind];
+            row.insertCell().textContent = entry.period || "-";
+            row.insertCell().textContent = entry.count;
+            entry.free();
+        }
     } catch (error) {
-        console.error("Error loading pulsar:", error);
+        console.error("Error running census:", error);
     }
 }
 
-function loadGliderGun() {
+function exportCensus() {
     if (!gameOfLife) return;
     try {
-        gameOfLife.load_glider_gun();
+        document.getElementById("patternText").value = gameOfLife.export_census();
+    } catch (error) {
+        console.error("Error exporting census:", error);
+    }
+}
+
+// The palette is built from the engine's pattern registry, grouped by
+// category, so registered patterns show up without extra handlers.
+function buildPatternPalette() {
+    const palette = document.getElementById("patternPalette");
+    palette.replaceChildren();
+    
+    const categories = new Map();
+    for (const info of gameOfLife.list_patterns()) {
+        if (!categories.has(info.category)) {
+            categories.set(info.category, []);
+        }
+        categories.get(info.category).push(info);
+    }
+    
+    for (const [category, patterns] of categories) {
+        const heading = document.createElement("div");
+        heading.className = "pattern-category";
+        heading.textContent = category;
+        palette.appendChild(heading);
+        
+        const grid = document.createElement("div");
+        grid.className = "pattern-grid";
+        for (const info of patterns) {
+            const button = document.createElement("button");
+            button.className = "pattern-btn";
+            button.textContent = info.name.toUpperCase();
+            button.title = `${info.description} (${info.width}x${info.height}, ${info.population} cells)`;
+            button.addEventListener("click", () => loadPattern(info.name));
+            grid.appendChild(button);
+            info.free();
+        }
+        palette.appendChild(grid);
+    }
+}
+
+function loadPattern(name) {
+    if (!gameOfLife) return;
+    try {
+        gameOfLife.load_pattern(name);
+        syncGridSettings();
         gameOfLife.render();
         generation = 0;
         updateStats();
     } catch (error) {
-        console.error("Error loading glider gun:", error);
+        console.error(`Error loading ${name}:`, error);
+    }
+}
+
+function registerPattern() {
+    if (!gameOfLife) return;
+    
+    const text = document.getElementById("patternText").value;
+    try {
+        gameOfLife.register_pattern("", "User", text);
+        buildPatternPalette();
+    } catch (error) {
+        console.error("Error registering pattern:", error);
     }
 }
 